@@ -0,0 +1,107 @@
+/*
+Endings, and the conditions that bring them about.
+
+A world can have any number of named endings. After every turn they are
+checked in order, and the first one whose conditions all hold ends the
+game.
+*/
+
+use uuid::Uuid;
+
+use game::gamestate::GameState;
+use obj::DamageLevel;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EndingKind {
+	WIN, LOSS, DEATH, NEUTRAL,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+	HOLDING(Uuid), // The player carries the item
+	REACHED(usize), // The player is in the location
+	DESTROYED(Uuid), // The item has been destroyed
+	TURNS(u32), // At least this many turns have passed
+	FLAG(String), // The flag has been set
+	DARK, // The player can't see
+}
+
+impl Condition {
+	pub fn holds(&self, game_state: &GameState) -> bool {
+		match *self {
+			Condition::HOLDING(id) => game_state.player().find_item_by_id(id).is_some(),
+			Condition::REACHED(l) => game_state.player().location == l,
+			Condition::DESTROYED(id) => match game_state.find_item_by_id(id) {
+				Some(i) => i.damage_level() == DamageLevel::DESTROYED,
+				None => false,
+			},
+			Condition::TURNS(t) => game_state.turns() >= t,
+			Condition::FLAG(ref f) => game_state.has_flag(f),
+			Condition::DARK => !game_state.can_see(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ending {
+	pub name: String,
+	pub text: String,
+	pub kind: EndingKind,
+	pub conditions: Vec<Condition>,
+}
+
+impl Ending {
+	/*
+	Creates an Ending with no conditions. An ending with no conditions
+	is never reached.
+	*/
+	pub fn new(name: &str, text: &str, kind: EndingKind) -> Ending {
+		Ending {
+			name: name.to_string(),
+			text: text.to_string(),
+			kind,
+			conditions: Vec::new(),
+		}
+	}
+
+	pub fn add_condition(&mut self, c: Condition) {
+		self.conditions.push(c);
+	}
+
+	pub fn is_reached(&self, game_state: &GameState) -> bool {
+		!self.conditions.is_empty() && self.conditions_hold(game_state)
+	}
+
+	/*
+	Returns true if every condition holds, including when there are none
+	*/
+	pub fn conditions_hold(&self, game_state: &GameState) -> bool {
+		self.conditions.iter().all(|c| c.holds(game_state))
+	}
+
+	/*
+	Returns true for the endings that let the player restart, restore or
+	undo
+	*/
+	pub fn is_loss(&self) -> bool {
+		self.kind == EndingKind::LOSS || self.kind == EndingKind::DEATH
+	}
+
+	/*
+	Formats the final summary screen shown when the game ends this way.
+	Worlds without points just give the number of turns.
+	*/
+	pub fn summary(&self, game_state: &GameState) -> String {
+		let played = if game_state.max_score() > 0 {
+			game_state.describe_score()
+		} else {
+			format!("You played for {} turn{}.", game_state.turns(), if game_state.turns() == 1 { "" } else { "s" })
+		};
+		format!("{}\n\n*** {} ***\n\nEnding: {}\n{}", self.text, match self.kind {
+			EndingKind::WIN => "You have won",
+			EndingKind::LOSS => "You have lost",
+			EndingKind::DEATH => "You have died",
+			EndingKind::NEUTRAL => "The End",
+		}, self.name, played)
+	}
+}
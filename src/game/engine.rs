@@ -0,0 +1,343 @@
+/*
+The game engine. It takes one line of input at a time and returns what
+happened, without reading or printing anything itself, so a frontend
+only has to pass lines in and show the Output.
+*/
+
+use std::path::{Path,PathBuf};
+
+use game::ending::{Ending,EndingKind};
+use game::gamecontroller;
+use game::gamestate::GameState;
+use game::hazard::Trigger;
+use parsing::{GameStateType,OtherType,CommandNode};
+use parsing::parser;
+
+const DEFAULT_SAVE_FILE: &str = "rustade.sav";
+const DEATH_PROMPT: &str = "Would you like to RESTART, RESTORE a saved game, UNDO your last move, or QUIT?";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameStatus {
+	PLAYING, WON, LOST, ENDED, QUIT,
+}
+
+/*
+A question the engine has asked, answered by the next line of input
+*/
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Question {
+	QUIT, RESTART, DEATH,
+}
+
+/*
+The result of one step:
+	text: blocks of text to show, in order
+	room_changed: true if the player is in a different location
+	status: whether the game is still going
+*/
+#[derive(Debug)]
+pub struct Output {
+	pub text: Vec<String>,
+	pub room_changed: bool,
+	pub status: GameStatus,
+}
+
+impl Output {
+	fn new() -> Output {
+		Output {
+			text: Vec::new(),
+			room_changed: false,
+			status: GameStatus::PLAYING,
+		}
+	}
+
+	/*
+	Adds a block of text, skipping empty ones
+	*/
+	fn push(&mut self, block: String) {
+		if !block.is_empty() {
+			self.text.push(block);
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Engine {
+	game_state: GameState,
+	question: Option<Question>,
+	status: GameStatus,
+	save_dir: PathBuf, // Where save files go, the current directory by default
+	ending: Option<Ending>,
+}
+
+impl Engine {
+	/*
+	Creates an Engine to play the given game.
+	*/
+	pub fn new(game_state: GameState) -> Engine {
+		Engine {
+			game_state,
+			question: None,
+			status: GameStatus::PLAYING,
+			save_dir: PathBuf::new(),
+			ending: None,
+		}
+	}
+
+	/*
+	Sets the directory save files are written to and restored from
+	*/
+	pub fn set_save_dir(&mut self, dir: &Path) {
+		self.save_dir = dir.to_path_buf();
+	}
+
+	/*
+	Returns the opening description of the game, after the world's
+	intro
+	*/
+	pub fn start(&mut self) -> Output {
+		let mut output = Output::new();
+		output.push(self.game_state.intro().to_string());
+		output.push(gamecontroller::look(&self.game_state));
+		output.push(self.game_state.update());
+		self.game_state.mark_visited();
+		output.room_changed = true;
+		output
+	}
+
+	/*
+	Carries out one line of input. If the engine asked a question, the
+	line is taken as the answer.
+	*/
+	pub fn step(&mut self, input: &str) -> Output {
+		let mut output = Output::new();
+		let old_location = self.game_state.player().location;
+
+		match self.question.take() {
+			Some(q) => {
+				let answer = self.answer(q, input);
+				output.push(answer);
+			},
+			None if self.status != GameStatus::PLAYING => {
+				output.push("The game is over.".to_string());
+			},
+			None => {
+				let command = parser::parse_line(input, self.game_state.lexicon());
+				match command {
+					Some(ref c) if gamecontroller::changes_game(c) => self.game_state.snapshot(input.trim()),
+					_ => (),
+				}
+				let mut took_turn = false;
+				let response = match command {
+					Some(CommandNode::GAMESTATE(s, file)) => self.gamestate(s, file),
+					Some(c) => {
+						took_turn = true;
+						self.game_state.next_turn();
+						gamecontroller::do_command(c, &mut self.game_state)
+					},
+					None => "I don't know how to do that.".to_string(),
+				};
+				output.push(response);
+				if took_turn {
+					output.push(self.game_state.run_events());
+				}
+				output.push(self.game_state.update());
+				if took_turn {
+					output.push(self.game_state.check_score());
+					output.push(self.game_state.burn_fuel());
+					self.game_state.check_hazards(Trigger::TURN);
+				}
+				let ending = match self.game_state.death() {
+					Some(d) => Some(d.clone()),
+					None => self.game_state.check_endings().cloned(),
+				};
+				if let Some(e) = ending {
+					self.finish(e, &mut output);
+				}
+			},
+		}
+
+		// Only after the output is written, so it can tell a first visit
+		self.game_state.mark_visited();
+		output.room_changed = self.game_state.player().location != old_location;
+		output.status = self.status;
+		output
+	}
+
+	/*
+	Ends the game. After a loss the player is asked whether to restart,
+	restore, undo or quit.
+	*/
+	fn finish(&mut self, ending: Ending, output: &mut Output) {
+		output.push(ending.summary(&self.game_state));
+		self.status = match ending.kind {
+			EndingKind::WIN => GameStatus::WON,
+			EndingKind::LOSS | EndingKind::DEATH => GameStatus::LOST,
+			EndingKind::NEUTRAL => GameStatus::ENDED,
+		};
+		if ending.is_loss() {
+			self.question = Some(Question::DEATH);
+			output.push(DEATH_PROMPT.to_string());
+		}
+		self.ending = Some(ending);
+	}
+
+	/*
+	Carries on playing after a loss has been restarted, restored or
+	undone
+	*/
+	fn resume(&mut self) {
+		self.status = GameStatus::PLAYING;
+		self.ending = None;
+	}
+
+	pub fn status(&self) -> GameStatus {
+		self.status
+	}
+
+	/*
+	Returns the ending the game finished with, if it has
+	*/
+	pub fn ending(&self) -> Option<&Ending> {
+		self.ending.as_ref()
+	}
+
+	/*
+	Returns true if the engine is waiting for an answer to a question
+	*/
+	pub fn is_asking(&self) -> bool {
+		self.question.is_some()
+	}
+
+	/*
+	Returns a pointer to the game being played
+	*/
+	pub fn game_state(&self) -> &GameState {
+		&self.game_state
+	}
+
+	fn gamestate(&mut self, s: GameStateType, file: Option<String>) -> String {
+		match s {
+			GameStateType::QUIT => {
+				self.question = Some(Question::QUIT);
+				"Are you sure you want to quit? Any unsaved progress will be lost. (yes/[no])".to_string()
+			},
+			GameStateType::RESTART => {
+				self.question = Some(Question::RESTART);
+				"Are you sure you want to restart? Any unsaved progress will be lost. (yes/[no])".to_string()
+			},
+			GameStateType::SAVE => match self.save_file(file) {
+				Ok((name, path)) => match self.game_state.save(&path) {
+					Ok(_) => format!("Saved to {}.", name),
+					Err(e) => e,
+				},
+				Err(e) => format!("{}\nThe game was not saved.", e),
+			},
+			GameStateType::RESTORE => match self.restore(file) {
+				Ok(s) | Err(s) => s,
+			},
+			GameStateType::UNDO => match self.game_state.undo() {
+				Some(c) => format!("Undone: {}\n\n{}", c, gamecontroller::look(&self.game_state)),
+				None => "There is nothing to undo.".to_string(),
+			},
+			GameStateType::SCORE => self.game_state.describe_score(),
+			GameStateType::SEED => format!("The seed is {}.", self.game_state.seed()),
+		}
+	}
+
+	/*
+	Restores the game from the save file the player named, returning
+	what to show either way
+	*/
+	fn restore(&mut self, file: Option<String>) -> Result<String, String> {
+		let restored = self.save_file(file).and_then(|(name, path)| self.game_state.restore(&path).map(|_| name));
+		match restored {
+			Ok(name) => Ok(format!("Restored from {}.\n\n{}", name, gamecontroller::look(&self.game_state))),
+			Err(e) => Err(format!("{}\nThe game was not restored.", e)),
+		}
+	}
+
+	/*
+	Works out the save file the player named, or the default one, and
+	returns its name and its path. Players name a file, not a path, so
+	every save stays in the save directory.
+	*/
+	fn save_file(&self, file: Option<String>) -> Result<(String, String), String> {
+		let name = file.unwrap_or_else(|| DEFAULT_SAVE_FILE.to_string());
+		if name.contains(['/', '\\', ':']) || name == "." || name == ".." {
+			return Err(format!("'{}' isn't a file name. Name the save file without a folder, like {}.", name, DEFAULT_SAVE_FILE));
+		}
+		let path = self.save_dir.join(&name).to_string_lossy().to_string();
+		Ok((name, path))
+	}
+
+	/*
+	Handles the answer to a question the engine asked
+	*/
+	fn answer(&mut self, q: Question, input: &str) -> String {
+		let command = parser::parse_line(input, self.game_state.lexicon());
+		match q {
+			Question::QUIT => match command {
+				Some(CommandNode::GAMESTATE(GameStateType::QUIT, _)) |
+				Some(CommandNode::OTHER(OtherType::YES)) => {
+					self.status = GameStatus::QUIT;
+					"Goodbye".to_string()
+				},
+				Some(CommandNode::OTHER(OtherType::NO)) => "Excellent.".to_string(),
+				_ => "I'll take that as a no.".to_string(),
+			},
+			Question::RESTART => match command {
+				Some(CommandNode::GAMESTATE(GameStateType::RESTART, _)) |
+				Some(CommandNode::OTHER(OtherType::YES)) => self.restart(),
+				Some(CommandNode::OTHER(OtherType::NO)) => "Excellent.".to_string(),
+				_ => "I'll take that as a no.".to_string(),
+			},
+			Question::DEATH => self.answer_death(command),
+		}
+	}
+
+	/*
+	Handles the choice after a loss. Anything that doesn't get the game
+	going again asks the question again.
+	*/
+	fn answer_death(&mut self, command: Option<CommandNode>) -> String {
+		let response = match command {
+			Some(CommandNode::GAMESTATE(GameStateType::RESTART, _)) => return self.restart(),
+			Some(CommandNode::GAMESTATE(GameStateType::QUIT, _)) => {
+				self.status = GameStatus::QUIT;
+				return "Goodbye".to_string();
+			},
+			Some(CommandNode::GAMESTATE(GameStateType::RESTORE, file)) => match self.restore(file) {
+				Ok(s) => {
+					self.resume();
+					return s;
+				},
+				Err(e) => e,
+			},
+			Some(CommandNode::GAMESTATE(GameStateType::UNDO, _)) => match self.game_state.undo() {
+				Some(c) => {
+					self.resume();
+					return format!("Undone: {}\n\n{}", c, gamecontroller::look(&self.game_state));
+				},
+				None => "There is nothing to undo.".to_string(),
+			},
+			_ => String::new(),
+		};
+		self.question = Some(Question::DEATH);
+		if response.is_empty() {
+			DEATH_PROMPT.to_string()
+		} else {
+			format!("{}\n\n{}", response, DEATH_PROMPT)
+		}
+	}
+
+	/*
+	Starts the game again from the beginning
+	*/
+	fn restart(&mut self) -> String {
+		self.game_state.restart();
+		self.resume();
+		format!("Restarting.\n\n{}", gamecontroller::look(&self.game_state))
+	}
+}
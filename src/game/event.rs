@@ -0,0 +1,90 @@
+/*
+Timed events, driven by the turn counter.
+
+An event is something the world does on its own. A fuse fires once, a
+number of turns after it starts ("in 5 turns the ceiling collapses"). A
+daemon fires over and over, every so many turns, until it is cancelled
+("every turn the water rises"). When an event fires, its text is shown
+and its effects are carried out.
+
+Events start when their conditions all hold, or with the game if they
+have none, and each only starts itself once. Effects can also start and
+cancel other events, so they can be chained. An event can be given a
+chance of firing, so that each time it is due it may not happen ("the
+floor sometimes shakes"). The chance is rolled with the game's rng.
+*/
+
+use uuid::Uuid;
+
+use game::ending::Condition;
+use game::gamestate::GameState;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timing {
+	FUSE(u32), // Fires once, this many turns after starting
+	DAEMON(u32), // Fires every this many turns
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Effect {
+	FLAG(String), // Sets the flag
+	DESTROY(Uuid), // Destroys the item
+	DESCRIBE(usize, String), // Changes the location's description
+	START(String), // Starts the event
+	CANCEL(String), // Stops the event
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+	pub id: String,
+	pub text: String,
+	pub timing: Timing,
+	pub conditions: Vec<Condition>,
+	pub effects: Vec<Effect>,
+	pub chance: u32, // The percent chance it fires each time it is due
+}
+
+impl Event {
+	/*
+	Creates an Event with no conditions or effects, which always fires
+	when it is due. An event with no conditions starts with the game.
+	*/
+	pub fn new(id: &str, text: &str, timing: Timing) -> Event {
+		Event {
+			id: id.to_string(),
+			text: text.to_string(),
+			timing,
+			conditions: Vec::new(),
+			effects: Vec::new(),
+			chance: 100,
+		}
+	}
+
+	pub fn add_condition(&mut self, c: Condition) {
+		self.conditions.push(c);
+	}
+
+	pub fn add_effect(&mut self, e: Effect) {
+		self.effects.push(e);
+	}
+
+	/*
+	Returns true if the event should start itself now
+	*/
+	pub fn is_ready(&self, game_state: &GameState) -> bool {
+		!self.conditions.is_empty() && self.conditions.iter().all(|c| c.holds(game_state))
+	}
+}
+
+/*
+A started event, waiting for its turn:
+	event: the id of the event
+	due: the turn it fires on
+	every: how often it fires again, or None for a fuse
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timer {
+	pub event: String,
+	pub due: u32,
+	pub every: Option<u32>,
+}
@@ -1,87 +1,64 @@
 
 
 use game::gamestate::GameState;
-use obj::DamageType;
-use parsing::{GameStateType,DirectionType,OtherType,CommandNode};
-use parsing::grammar::{ItemNode,PrepositionNode};
-use parsing::parser;
-
-pub fn take_control(mut game_state: GameState) -> Option<CommandNode> {
-	println!("\n{}", look(&mut game_state));
-	println!("{}", game_state.update());
-	let mut command: Option<CommandNode>;
-	'control: loop {
-		command = parser::get_next_command();
-		println!("\n{}", match command.clone() {
-			Some(c) => match c {
-				CommandNode::GAMESTATE(s) => gamestate(s, &mut game_state),
-				CommandNode::INVENTORY => inventory(&mut game_state),
-				CommandNode::LOOK(prep) => match prep {
-					Some(p_node) => {
-						item_look(p_node, &mut game_state)
-					},
-					None => look(&mut game_state),
-				},
-				CommandNode::GO(d) => go(d, &mut game_state),
-				CommandNode::TAKE(i_node) => get(i_node, &mut game_state),
-				CommandNode::DROP(i_node) => drop(i_node, &mut game_state),
-				CommandNode::EXAMINE(i_node) => examine(i_node, &game_state),
-				CommandNode::USE(i_node) => use_item(i_node, &mut game_state),
-				_ => "Not implemented.".to_string(),
-			},
-			None => "I don't know how to do that.".to_string(),
-		});
-		println!("{}", game_state.update());
-		if game_state.update_player() {
-			println!("You win!\n\n");
-			break 'control;
-		}
-		/*words = input.split_whitespace().collect::<Vec<&str>>();
-		words.push("end");
-		for word in &words {
-			test = token::Token::new(word);
-			println!("{}", test);
-			if test.t_type == token::TokenType::QUIT {
-				break 'control;
-			}
-		}*/
-		if game_state.break_control {
-			break 'control;
-		}
-	}
-	command
-}
+use game::hazard::Trigger;
+use obj::door::Door;
+use obj::item::{Item,list_item_names};
+use obj::{DamageType,DamageLevel};
+use parsing::{PrepositionType,DirectionType,CommandNode};
+use parsing::grammar::{ItemNode,ItemListNode,PrepositionNode,ItemToItemNode};
 
-fn gamestate(s: GameStateType, game_state: &mut GameState) -> String {
-	match s {
-		GameStateType::QUIT => match quit() {
-			Some(yes) => {
-				if yes {
-					game_state.break_control = true;
-					"Goodbye".to_string()
-				} else {
-					"Excellent.".to_string()
-				}
+const DARK_DESC: &str = "It is pitch black. You can't see a thing.";
+
+/*
+Carries out a command that acts on the world, returning what happened.
+Game state commands (quit, save, restore and undo) are handled by the
+Engine.
+*/
+pub fn do_command(command: CommandNode, game_state: &mut GameState) -> String {
+	match command {
+		CommandNode::TAKE(_) | CommandNode::EXAMINE(_) | CommandNode::LOOK(_) if !game_state.can_see() => {
+			DARK_DESC.to_string()
+		},
+		CommandNode::INVENTORY => inventory(game_state),
+		CommandNode::LOOK(prep) => match prep {
+			Some(p_node) => {
+				item_look(p_node, game_state)
 			},
-			None => "I'll take that as a no.".to_string(),
+			None => look(game_state),
 		},
+		CommandNode::GO(d) => go(d, game_state),
+		CommandNode::JUMP(i_node) => jump(i_node, game_state),
+		CommandNode::TAKE(il_node) => get_list(il_node, game_state),
+		CommandNode::DROP(il_node) => drop_list(il_node, game_state),
+		CommandNode::EXAMINE(i_node) => examine(i_node, game_state),
+		CommandNode::USE(i_node) => use_item(i_node, game_state),
+		CommandNode::CUT(ii_node) => damage_with(ii_node, DamageType::CUTTING, game_state),
+		CommandNode::HIT(ii_node) => damage_with(ii_node, DamageType::SMASHING, game_state),
+		CommandNode::TIE(ii_node) => tie(ii_node, game_state),
+		CommandNode::PUT(ii_node) => put(ii_node, game_state),
+		CommandNode::OPEN(i_node) => open(i_node, game_state),
+		CommandNode::CLOSE(i_node) => close(i_node, game_state),
+		CommandNode::LOCK(i_node, key) => lock(i_node, key, true, game_state),
+		CommandNode::UNLOCK(i_node, key) => lock(i_node, key, false, game_state),
+		CommandNode::TURNON(i_node) => turn(i_node, true, game_state),
+		CommandNode::TURNOFF(i_node) => turn(i_node, false, game_state),
+		_ => "Not implemented.".to_string(),
 	}
 }
 
-fn quit() -> Option<bool> {
-	println!("{}", "\nAre you sure you want to quit? Your game will not be saved. (yes/[no])\n");
-	match parser::get_next_command() {
-		Some(c) => match c {
-			CommandNode::GAMESTATE(s) => match s {
-				GameStateType::QUIT => Some(true),
-			},
-			CommandNode::OTHER(o) => match o {
-				OtherType::YES => Some(true),
-				OtherType::NO => Some(false),
-			},
-			_ => None,
-		},
-		None => None,
+/*
+Returns true if the command can change the game, and so should be
+undoable. Game state commands, and commands that only look around,
+don't use up undo slots.
+*/
+pub fn changes_game(c: &CommandNode) -> bool {
+	match *c {
+		CommandNode::GAMESTATE(_, _) | CommandNode::OTHER(_) => false,
+		CommandNode::INVENTORY | CommandNode::EXAMINE(_) => false,
+		CommandNode::LOOK(None) => false,
+		CommandNode::LOOK(Some(ref p_node)) => matches!(p_node.prep, PrepositionType::UNDER | PrepositionType::BEHIND),
+		_ => true,
 	}
 }
 
@@ -89,19 +66,82 @@ fn inventory(game_state: &mut GameState) -> String {
 	game_state.player().display_inventory()
 }
 
-fn look(game_state: &mut GameState) -> String {
-	format!("{}", game_state.current_location())
+pub fn look(game_state: &GameState) -> String {
+	if game_state.can_see() {
+		game_state.render(game_state.current_location())
+	} else {
+		DARK_DESC.to_string()
+	}
 }
 
+/*
+Handles look at/in/on/under/behind. Looking under or behind an item
+reveals anything hidden there, leaving it in the room to be taken.
+*/
 fn item_look(p_node: PrepositionNode, game_state: &mut GameState) -> String {
-	"Looking for item...".to_string()
+	let prep = preposition_name(p_node.prep);
+	let item = match game_state.has_item(&p_node.item) {
+		Some(i) => i,
+		None => return format!("You don't see a {} here.", p_node.item.subject_lexeme),
+	};
+	match p_node.prep {
+		PrepositionType::AT => examine(p_node.item, game_state),
+		PrepositionType::ON => {
+			if item.on_items.is_empty() {
+				format!("There is nothing on the {}.", p_node.item.subject_lexeme)
+			} else {
+				format!("On the {} is {}.", p_node.item.subject_lexeme, list_item_names(&item.on_items))
+			}
+		},
+		PrepositionType::IN => {
+			if !item.has_inside {
+				format!("You can't see inside the {}.", p_node.item.subject_lexeme)
+			} else if !item.is_inside_visible() {
+				format!("The {} is closed.", p_node.item.subject_lexeme)
+			} else if item.in_items.is_empty() {
+				format!("The {} is empty.", p_node.item.subject_lexeme)
+			} else {
+				format!("In the {} is {}.", p_node.item.subject_lexeme, list_item_names(&item.in_items))
+			}
+		},
+		PrepositionType::UNDER | PrepositionType::BEHIND => {
+			match game_state.reveal_items(&p_node.item, p_node.prep) {
+				Some(ref found) if !found.is_empty() => {
+					let mut display = format!("{} the {} you find {}.", prep, p_node.item.subject_lexeme, list_item_names(found));
+					display[..1].make_ascii_uppercase();
+					display
+				},
+				_ => format!("You find nothing {} the {}.", prep, p_node.item.subject_lexeme),
+			}
+		},
+		_ => format!("You can't look {} the {}.", prep, p_node.item.subject_lexeme),
+	}
+}
+
+/*
+Jumps on the spot, or into or across an item. Some jumps are fatal.
+*/
+fn jump(i_node: Option<ItemNode>, game_state: &mut GameState) -> String {
+	match i_node {
+		Some(i_node) => match game_state.has_item(&i_node) {
+			Some(item) => {
+				if game_state.check_hazards(Trigger::JUMP(item.get_id())) {
+					String::new()
+				} else {
+					format!("There's no reason to jump over the {}.", i_node.subject_lexeme)
+				}
+			},
+			None => format!("You don't see a {} here.", i_node.subject_lexeme),
+		},
+		None => "You jump on the spot. Nothing happens.".to_string(),
+	}
 }
 
 fn go(d: DirectionType, game_state: &mut GameState) -> String {
 	format!("{}{}", game_state.current_location().display_exiting(d),
 		match game_state.move_player(d) {
 			Some(_) => {
-				format!("\n{}", game_state.current_location())
+				format!("\n{}", look(game_state))
 			},
 			None => "".to_string()
 		}
@@ -111,7 +151,7 @@ fn go(d: DirectionType, game_state: &mut GameState) -> String {
 fn get(i_node: ItemNode, game_state: &mut GameState) -> String {
 	match game_state.get_item(&i_node) {
 		Some(i) => {
-			if !i.is_fixed {
+			if !i.is_fixed && !i.is_scenery {
 				format!("You pick up the {}.", i_node.subject_lexeme)
 			} else {
 				format!("You can't pick up the {}.", i_node.subject_lexeme)
@@ -121,6 +161,112 @@ fn get(i_node: ItemNode, game_state: &mut GameState) -> String {
 	}
 }
 
+/*
+Takes every item in the list, giving each item its own result line. A
+single named item gets the usual sentence instead.
+*/
+fn get_list(il_node: ItemListNode, game_state: &mut GameState) -> String {
+	let container = match il_node.from {
+		Some(ref c_node) => match game_state.has_item(c_node) {
+			Some(c) => {
+				if !c.is_inside_visible() {
+					return format!("The {} is closed.", c_node.subject_lexeme);
+				}
+				Some(c)
+			},
+			None => return format!("You don't see a {} here.", c_node.subject_lexeme),
+		},
+		None => None,
+	};
+	if let Some(i_node) = il_node.single() {
+		return match il_node.from {
+			Some(ref c_node) => get_from(i_node.clone(), c_node, game_state),
+			None => get(i_node.clone(), game_state),
+		};
+	}
+	let i_nodes: Vec<ItemNode> = if il_node.all {
+		match container {
+			Some(ref c) => c.visible_items().into_iter()
+				.filter(|i| !i.is_fixed)
+				.map(ItemNode::from_item)
+				.collect(),
+			None => game_state.current_location().items().iter()
+				.filter(|i| !i.is_fixed)
+				.map(ItemNode::from_item)
+				.collect(),
+		}
+	} else {
+		il_node.items.clone()
+	};
+	let mut results = Vec::new();
+	for i_node in i_nodes.iter().filter(|n| !il_node.is_excepted(n)) {
+		let taken = match il_node.from {
+			Some(ref c_node) => game_state.get_item_from(i_node, c_node),
+			None => game_state.get_item(i_node),
+		};
+		results.push(format!("{}: {}", i_node.subject_lexeme, match taken {
+			Some(i) => {
+				if !i.is_fixed && !i.is_scenery {
+					"Taken."
+				} else {
+					"You can't pick that up."
+				}
+			},
+			None => match game_state.player().find_item(i_node) {
+				Some(_) => "You already have that.",
+				None => "You don't see that here.",
+			},
+		}));
+	}
+	if results.is_empty() {
+		"There is nothing here to take.".to_string()
+	} else {
+		results.join("\n")
+	}
+}
+
+/*
+Drops every item in the list, giving each item its own result line. A
+single named item gets the usual sentence instead.
+*/
+fn drop_list(il_node: ItemListNode, game_state: &mut GameState) -> String {
+	if let Some(i_node) = il_node.single() {
+		return drop(i_node.clone(), game_state);
+	}
+	let i_nodes: Vec<ItemNode> = if il_node.all {
+		game_state.player().inventory.iter()
+			.map(ItemNode::from_item)
+			.collect()
+	} else {
+		il_node.items.clone()
+	};
+	let mut results = Vec::new();
+	for i_node in i_nodes.iter().filter(|n| !il_node.is_excepted(n)) {
+		results.push(format!("{}: {}", i_node.subject_lexeme, match game_state.drop_item(i_node) {
+			Some(_) => "Dropped.",
+			None => "You aren't carrying that.",
+		}));
+	}
+	if results.is_empty() {
+		"You aren't carrying anything to drop.".to_string()
+	} else {
+		results.join("\n")
+	}
+}
+
+fn get_from(i_node: ItemNode, c_node: &ItemNode, game_state: &mut GameState) -> String {
+	match game_state.get_item_from(&i_node, c_node) {
+		Some(i) => {
+			if !i.is_fixed && !i.is_scenery {
+				format!("You take the {} from the {}.", i_node.subject_lexeme, c_node.subject_lexeme)
+			} else {
+				format!("You can't pick up the {}.", i_node.subject_lexeme)
+			}
+		},
+		None => format!("You don't see a {} on or in the {}.", i_node.subject_lexeme, c_node.subject_lexeme),
+	}
+}
+
 fn drop(i_node: ItemNode, game_state: &mut GameState) -> String {
 	match game_state.drop_item(&i_node) {
 		Some(_) => "Dropped.".to_string(),
@@ -130,27 +276,289 @@ fn drop(i_node: ItemNode, game_state: &mut GameState) -> String {
 
 fn examine(i_node: ItemNode, game_state: &GameState) -> String {
 	match game_state.has_item(&i_node) {
-		Some(i) => format!("{}", i),
+		Some(i) => format!("{}{}", game_state.render(&i), i.display_contents()),
+		None => match game_state.find_door(&i_node) {
+			Some(door) => format!("{}", door),
+			None => format!("You don't see a {} here.", i_node.subject_lexeme),
+		},
+	}
+}
+
+/*
+Turns an item on or off. Turning a light source on or off in a dark
+place also shows what can now be seen.
+*/
+fn turn(i_node: ItemNode, b: bool, game_state: &mut GameState) -> String {
+	let state = if b { "on" } else { "off" };
+	match game_state.has_item(&i_node) {
+		Some(ref i) if !i.can_turn_on() => format!("You can't turn that {}.", state),
+		Some(ref i) if i.is_on() == b => format!("The {} is already {}.", i_node.subject_lexeme, state),
+		Some(_) => {
+			let could_see = game_state.can_see();
+			match game_state.set_item_on(&i_node, b) {
+				Some(true) => {
+					let mut display = format!("You turn {} the {}.", state, i_node.subject_lexeme);
+					if could_see != game_state.can_see() {
+						display.push_str(format!("\n\n{}", look(game_state)).as_str());
+					}
+					display
+				},
+				Some(false) => format!("The {} has run out of fuel.", i_node.subject_lexeme),
+				None => format!("You can't turn that {}.", state),
+			}
+		},
+		None => format!("You don't see a {} here.", i_node.subject_lexeme),
+	}
+}
+
+fn open(i_node: ItemNode, game_state: &mut GameState) -> String {
+	if let Some(door) = game_state.find_door(&i_node) {
+		return open_door(door, true, game_state);
+	}
+	match game_state.has_item(&i_node) {
+		Some(_) => match game_state.open_item(&i_node, true) {
+			Some(true) => match game_state.has_item(&i_node) {
+				Some(i) => format!("You open the {}.{}", i_node.subject_lexeme, i.display_contents()),
+				None => format!("You open the {}.", i_node.subject_lexeme),
+			},
+			Some(false) => format!("The {} is already open.", i_node.subject_lexeme),
+			None => format!("You can't open the {}.", i_node.subject_lexeme),
+		},
 		None => format!("You don't see a {} here.", i_node.subject_lexeme),
 	}
 }
 
+fn close(i_node: ItemNode, game_state: &mut GameState) -> String {
+	if let Some(door) = game_state.find_door(&i_node) {
+		return open_door(door, false, game_state);
+	}
+	match game_state.has_item(&i_node) {
+		Some(_) => match game_state.open_item(&i_node, false) {
+			Some(true) => format!("You close the {}.", i_node.subject_lexeme),
+			Some(false) => format!("The {} is already closed.", i_node.subject_lexeme),
+			None => format!("You can't close the {}.", i_node.subject_lexeme),
+		},
+		None => format!("You don't see a {} here.", i_node.subject_lexeme),
+	}
+}
+
+/*
+Opens or closes a door, on both sides at once. Locked doors won't open.
+*/
+fn open_door(mut door: Door, b: bool, game_state: &mut GameState) -> String {
+	let verb = if b { "open" } else { "close" };
+	if door.is_open == b {
+		format!("The {} is already {}.", door.i_type, if b { "open" } else { "closed" })
+	} else if b && door.is_locked {
+		door.state_desc()
+	} else {
+		door.is_open = b;
+		game_state.update_door(&door);
+		format!("You {} the {}.", verb, door.i_type)
+	}
+}
+
+/*
+Handles lock and unlock. Only doors have locks, and the player must be
+carrying the door's key, either named or found in the inventory.
+*/
+fn lock(i_node: ItemNode, key: Option<ItemNode>, b: bool, game_state: &mut GameState) -> String {
+	let verb = if b { "lock" } else { "unlock" };
+	let mut door = match game_state.find_door(&i_node) {
+		Some(d) => d,
+		None => return match game_state.has_item(&i_node) {
+			Some(_) => format!("You can't {} the {}.", verb, i_node.subject_lexeme),
+			None => format!("You don't see a {} here.", i_node.subject_lexeme),
+		},
+	};
+	let key_id = match door.key {
+		Some(id) => id,
+		None => return format!("The {} has no lock.", door.i_type),
+	};
+	let key = match key {
+		Some(k_node) => match game_state.player().find_item(&k_node) {
+			Some(k) if k.get_id() == key_id => k,
+			Some(_) => return format!("The {} doesn't fit the {}.", k_node.subject_lexeme, door.i_type),
+			None => return format!("You aren't carrying a {}.", k_node.subject_lexeme),
+		},
+		None => match game_state.player().find_item_by_id(key_id) {
+			Some(k) => k,
+			None => return format!("You don't have the key to the {}.", door.i_type),
+		},
+	};
+	if door.is_locked == b {
+		format!("The {} is already {}.", door.i_type, if b { "locked" } else { "unlocked" })
+	} else if b && door.is_open {
+		format!("You'll have to close the {} first.", door.i_type)
+	} else {
+		door.is_locked = b;
+		game_state.update_door(&door);
+		format!("You {} the {} with the {}.", verb, door.i_type, key.i_type)
+	}
+}
+
+/*
+Handles use. A tool is used on the one thing here it can damage, and
+something that ties is tied to the one thing here it can be tied to; if
+there are several, the player is asked to say which with cut, hit or
+tie. Otherwise the item is turned on or off.
+*/
 fn use_item(i_node: ItemNode, game_state: &mut GameState) -> String {
 	match game_state.has_item(&i_node) {
-		Some(sub) => match game_state.damage_first_item(sub.damage_type.clone()) {
-			Some(obj) => format!("You {} the {} with the {}.", match sub.damage_type.clone().unwrap() {
-				DamageType::SMASHING => "smash".to_string(),
-				DamageType::CUTTING => "cut".to_string(),
-			}, obj.i_type, sub.i_type),
-			None => match game_state.attach_first_item(&i_node, sub.clone()) {
-				Some(obj) => format!("You attach the {} to the {}.", sub.i_type, obj.i_type),
-				None => match game_state.turn_on_item(&i_node) {
-					Some(b) if b => format!("You turn on the {}.", sub.i_type),
-					Some(_) => format!("You turn off the {}.", sub.i_type),
-					None => format!("You don't see anything you can use the {} on.", sub.i_type),
-				}
+		Some(sub) => match use_tool(&sub, game_state) {
+			Some(response) => response,
+			None => match use_tie(&sub, &i_node, game_state) {
+				Some(response) => response,
+				None if sub.can_turn_on() => turn(i_node, !sub.is_on(), game_state),
+				None => format!("You don't see anything you can use the {} on.", sub.i_type),
 			},
 		},
 		None => format!("You aren't carrying a {}.", i_node.subject_lexeme),
 	}
+}
+
+/*
+Ties sub to the one thing in the location it can be tied to: something
+fixed in place that takes a tie and has nothing tied to it yet. Returns
+None if sub doesn't tie or there is nothing to tie it to.
+*/
+fn use_tie(sub: &Item, i_node: &ItemNode, game_state: &mut GameState) -> Option<String> {
+	if !sub.can_attach {
+		return None;
+	}
+	let targets: Vec<Item> = game_state.current_location().all_items().into_iter()
+		.filter(|i| i.can_attach && (i.is_fixed || i.is_scenery) && i.attached_items.is_empty() && i.get_id() != sub.get_id())
+		.cloned()
+		.collect();
+	match targets.len() {
+		0 => None,
+		1 => {
+			Some(tie(ItemToItemNode::new(i_node.clone(), PrepositionType::TO, ItemNode::from_item(&targets[0])), game_state))
+		},
+		_ => {
+			let names: Vec<String> = targets.iter().map(|t| format!("the {}", t.i_type)).collect();
+			Some(format!("You could tie the {} to {}. Say which, as in \"tie {} to {}\".", sub.i_type, names.join(" or "), sub.i_type, targets[0].i_type))
+		},
+	}
+}
+
+/*
+Uses sub on whatever in the location it can damage. Returns None if sub
+isn't a tool or there is nothing for it to damage.
+*/
+fn use_tool(sub: &Item, game_state: &mut GameState) -> Option<String> {
+	let damage_type = sub.damage_type.clone()?;
+	let (verb, command) = match damage_type {
+		DamageType::SMASHING => ("smash", "hit"),
+		DamageType::CUTTING => ("cut", "cut"),
+	};
+	let targets: Vec<Item> = game_state.current_location().all_items().into_iter()
+		.filter(|i| i.damaged_by == Some(damage_type.clone()) && i.damage_level() != DamageLevel::DESTROYED)
+		.cloned()
+		.collect();
+	match targets.len() {
+		0 => None,
+		1 => game_state.damage_item_by_id(targets[0].get_id(), damage_type)
+			.map(|obj| format!("You {} the {} with the {}.", verb, obj.i_type, sub.i_type)),
+		_ => {
+			let names: Vec<String> = targets.iter().map(|t| format!("the {}", t.i_type)).collect();
+			Some(format!("You could {} {} with the {}. Say which, as in \"{} {} with {}\".", verb, names.join(" or "), sub.i_type, command, targets[0].i_type, sub.i_type))
+		},
+	}
+}
+
+/*
+Handles cut and hit: damages the subject with the object, as long as the
+object deals the right kind of damage and the subject can take it.
+*/
+fn damage_with(ii_node: ItemToItemNode, damage_type: DamageType, game_state: &mut GameState) -> String {
+	let verb = match damage_type {
+		DamageType::SMASHING => "smash",
+		DamageType::CUTTING => "cut",
+	};
+	let tool = match game_state.player().find_item(&ii_node.object) {
+		Some(i) => i,
+		None => return format!("You aren't carrying a {}.", ii_node.object.subject_lexeme),
+	};
+	let target = match game_state.has_item(&ii_node.subject) {
+		Some(i) => i,
+		None => return format!("You don't see a {} here.", ii_node.subject.subject_lexeme),
+	};
+	if tool.damage_type != Some(damage_type.clone()) {
+		format!("You can't {} anything with the {}.", verb, ii_node.object.subject_lexeme)
+	} else if target.damaged_by != Some(damage_type.clone()) {
+		format!("You can't {} the {}.", verb, ii_node.subject.subject_lexeme)
+	} else if target.damage_level() == DamageLevel::DESTROYED {
+		format!("There isn't enough left of the {} to {}.", ii_node.subject.subject_lexeme, verb)
+	} else {
+		match game_state.damage_item(&ii_node.subject, damage_type) {
+			Some(_) => format!("You {} the {} with the {}.", verb, ii_node.subject.subject_lexeme, ii_node.object.subject_lexeme),
+			None => format!("You can't {} the {}.", verb, ii_node.subject.subject_lexeme),
+		}
+	}
+}
+
+/*
+Handles tie: attaches the subject (from the inventory) to the object.
+*/
+fn tie(ii_node: ItemToItemNode, game_state: &mut GameState) -> String {
+	let sub = match game_state.player().find_item(&ii_node.subject) {
+		Some(i) => i,
+		None => return format!("You aren't carrying a {}.", ii_node.subject.subject_lexeme),
+	};
+	if !sub.can_attach {
+		return format!("You can't tie the {} to anything.", ii_node.subject.subject_lexeme);
+	}
+	match game_state.has_item(&ii_node.object) {
+		Some(_) => match game_state.attach_item(&ii_node.subject, &ii_node.object) {
+			Some(_) => format!("You tie the {} to the {}.", ii_node.subject.subject_lexeme, ii_node.object.subject_lexeme),
+			None => format!("You can't tie anything to the {}.", ii_node.object.subject_lexeme),
+		},
+		None => format!("You don't see a {} here.", ii_node.object.subject_lexeme),
+	}
+}
+
+/*
+Handles put: moves the subject from the inventory in/on/under/behind the
+object.
+*/
+fn put(ii_node: ItemToItemNode, game_state: &mut GameState) -> String {
+	let prep = preposition_name(ii_node.prep);
+	if game_state.player().find_item(&ii_node.subject).is_none() {
+		return format!("You aren't carrying a {}.", ii_node.subject.subject_lexeme);
+	}
+	if ii_node.subject.subject == ii_node.object.subject {
+		return format!("You can't put the {} {} itself.", ii_node.subject.subject_lexeme, prep);
+	}
+	match game_state.has_item(&ii_node.object) {
+		Some(obj) => {
+			if !obj.can_hold(ii_node.prep) {
+				if ii_node.prep == PrepositionType::IN && obj.has_inside {
+					format!("The {} is closed.", ii_node.object.subject_lexeme)
+				} else {
+					format!("You can't put anything {} the {}.", prep, ii_node.object.subject_lexeme)
+				}
+			} else if game_state.put_item(&ii_node.subject, ii_node.prep, &ii_node.object) {
+				format!("You put the {} {} the {}.", ii_node.subject.subject_lexeme, prep, ii_node.object.subject_lexeme)
+			} else {
+				format!("You can't put the {} {} the {}.", ii_node.subject.subject_lexeme, prep, ii_node.object.subject_lexeme)
+			}
+		},
+		None => format!("You don't see a {} here.", ii_node.object.subject_lexeme),
+	}
+}
+
+fn preposition_name(p: PrepositionType) -> &'static str {
+	match p {
+		PrepositionType::AT => "at",
+		PrepositionType::ON => "on",
+		PrepositionType::IN => "in",
+		PrepositionType::WITH => "with",
+		PrepositionType::UNDER => "under",
+		PrepositionType::BEHIND => "behind",
+		PrepositionType::TO => "to",
+		PrepositionType::FROM => "from",
+		PrepositionType::OVER => "over",
+		PrepositionType::OFF => "off",
+	}
 }
\ No newline at end of file
@@ -1,28 +1,419 @@
 
 
-use obj::DamageType;
-use obj::item::Item;
-use obj::location::Location;
+use std::collections::{HashSet,VecDeque};
+use std::fmt;
+use std::fs;
+use std::rc::Rc;
+use uuid::Uuid;
+
+use game::ending::{Ending,Condition};
+use game::event::{Event,Effect,Timing,Timer};
+use game::hazard::{Hazard,Trigger};
+use game::rng::Rng;
+use game::save::{SaveWriter,SaveReader,fingerprint};
+use game::score::{self,Milestone};
+use obj::{DamageType,DamageLevel};
+use obj::door::Door;
+use obj::item::{Item,ItemKey,save_items,load_items};
+use obj::location::{Location,LocationKey};
 use obj::player::Player;
+use obj::template::{self,Context,Seen};
 use parsing::grammar::ItemNode;
-use parsing::DirectionType;
+use parsing::lexicon::Lexicon;
+use parsing::{DirectionType,PrepositionType};
+use world::World;
+
+pub const DEFAULT_UNDO_DEPTH: usize = 10;
+
+/*
+A copy of everything a command can change, taken before the command is
+carried out so that it can be undone
+*/
+#[derive(Debug, Clone)]
+struct Snapshot {
+	command: String,
+	player: Player,
+	locations: Vec<Location>,
+	lexicon: Lexicon,
+	turns: u32,
+	flags: HashSet<String>,
+	timers: Vec<Timer>,
+	started: HashSet<String>,
+	score: i32,
+	milestones: HashSet<Milestone>,
+	rng: Rng,
+}
+
+/*
+The position a game is in, as the solver compares them. Only what can
+change what happens next is kept: timers are counted from now, and
+turns are only kept, up to the last one that matters, when an ending
+or event waits for them.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StateKey {
+	turns: Option<u32>,
+	flags: Vec<String>,
+	started: Vec<String>,
+	timers: Vec<(String, u32, Option<u32>)>, // Each timer's event, turns until due and repeat
+	score: i32,
+	milestones: Vec<Milestone>,
+	rng: Rng,
+	player_location: usize,
+	inventory: Vec<ItemKey>,
+	locations: Vec<LocationKey>,
+}
 
+#[derive(Debug, Clone)]
 pub struct GameState {
-	pub break_control: bool,
 	player: Player,
 	locations: Vec<Location>,
+	lexicon: Lexicon,
+	world_id: String,
+	endings: Vec<Ending>,
+	hazards: Vec<Hazard>,
+	events: Vec<Event>,
+	death: Option<Ending>,
+	start: usize,
+	initial: Rc<World>, // Shared by copies of the game, which never change it
+	turns: u32,
+	flags: HashSet<String>,
+	timers: Vec<Timer>, // Events waiting to fire
+	started: HashSet<String>, // Events that have started themselves
+	score: i32,
+	max_score: i32,
+	milestones: HashSet<Milestone>, // What has already scored
+	rng: Rng,
+	initial_rng: Rng, // The rng as the game began, for restarting
+	history: VecDeque<Snapshot>,
+	undo_depth: usize,
 }
 
 impl GameState {
 	/*
-	Creates a GameState instance.
+	Creates a GameState instance. The item vocabulary is built from the
+	items in the locations.
 	*/
 	pub fn new(l: usize, loc: Vec<Location>) -> GameState {
-		GameState {
-			break_control: false,
+		GameState::build(l, World::new(loc))
+	}
+
+	/*
+	Creates a GameState for a loaded world, starting in its first
+	location.
+	*/
+	pub fn from_world(world: World) -> GameState {
+		GameState::build(0, world)
+	}
+
+	/*
+	Creates a GameState starting at location l. A copy of the world is
+	kept so the game can be restarted. Events without conditions start
+	straight away, and the starting location counts as entered.
+	*/
+	fn build(l: usize, world: World) -> GameState {
+		let mut game_state = GameState {
 			player: Player::new(l),
-			locations: loc,
+			lexicon: Lexicon::from_locations(&world.locations),
+			world_id: world_id(&world.locations),
+			locations: world.locations.clone(),
+			endings: world.endings.clone(),
+			hazards: world.hazards.clone(),
+			events: world.events.clone(),
+			death: None,
+			start: l,
+			initial: Rc::new(world),
+			turns: 0,
+			flags: HashSet::new(),
+			timers: Vec::new(),
+			started: HashSet::new(),
+			score: 0,
+			max_score: 0,
+			milestones: HashSet::new(),
+			history: VecDeque::new(),
+			undo_depth: DEFAULT_UNDO_DEPTH,
+			rng: Rng::new(0),
+			initial_rng: Rng::new(0),
+		};
+		let ids: Vec<String> = game_state.events.iter()
+			.filter(|e| e.conditions.is_empty())
+			.map(|e| e.id.clone())
+			.collect();
+		for id in ids {
+			game_state.start_event(&id);
+		}
+		game_state.max_score = game_state.locations.iter()
+			.flat_map(|l| l.all_items().into_iter()
+				.flat_map(|i| vec![i.take_points, i.destroy_points])
+				.chain(Some(l.points)))
+			.filter(|p| *p > 0)
+			.sum();
+		game_state.check_score();
+		game_state
+	}
+
+	/*
+	Rebuilds the world as it was at the start of the game. The undo
+	depth and the rng are kept, with the rng back where it began, but
+	there is nothing left to undo.
+	*/
+	pub fn restart(&mut self) {
+		let undo_depth = self.undo_depth;
+		let rng = self.initial_rng.clone();
+		*self = GameState::build(self.start, (*self.initial).clone());
+		self.undo_depth = undo_depth;
+		self.set_rng(rng);
+	}
+
+	/*
+	Gives the game its random number generator. The game starts with
+	one seeded with 0, so games are the same every time unless they are
+	given another. A world generated with rng before the game begins
+	should hand it over afterwards, so one seed decides everything.
+	*/
+	pub fn set_rng(&mut self, rng: Rng) {
+		self.initial_rng = rng.clone();
+		self.rng = rng;
+	}
+
+	/*
+	Returns the game's random number generator, for anything that needs
+	a random choice
+	*/
+	pub fn rng(&mut self) -> &mut Rng {
+		&mut self.rng
+	}
+
+	/*
+	Returns the seed the game's random numbers come from
+	*/
+	pub fn seed(&self) -> u64 {
+		self.rng.seed()
+	}
+
+	/*
+	Sets how many commands can be undone. Zero turns undo off.
+	*/
+	pub fn set_undo_depth(&mut self, depth: usize) {
+		self.undo_depth = depth;
+		while self.history.len() > depth {
+			self.history.pop_front();
+		}
+	}
+
+	/*
+	Remembers the game as it is before command is carried out, dropping
+	the oldest snapshot once there are more than the undo depth
+	*/
+	pub fn snapshot(&mut self, command: &str) {
+		if self.undo_depth == 0 {
+			return;
+		}
+		if self.history.len() == self.undo_depth {
+			self.history.pop_front();
+		}
+		self.history.push_back(Snapshot {
+			command: command.to_string(),
+			player: self.player.clone(),
+			locations: self.locations.clone(),
+			lexicon: self.lexicon.clone(),
+			turns: self.turns,
+			flags: self.flags.clone(),
+			timers: self.timers.clone(),
+			started: self.started.clone(),
+			score: self.score,
+			milestones: self.milestones.clone(),
+			rng: self.rng.clone(),
+		});
+	}
+
+	/*
+	Puts the game back to how it was before the last command, returning
+	the command that was undone, or None if there is nothing to undo
+	*/
+	pub fn undo(&mut self) -> Option<String> {
+		match self.history.pop_back() {
+			Some(s) => {
+				self.player = s.player;
+				self.locations = s.locations;
+				self.lexicon = s.lexicon;
+				self.turns = s.turns;
+				self.flags = s.flags;
+				self.timers = s.timers;
+				self.started = s.started;
+				self.score = s.score;
+				self.milestones = s.milestones;
+				self.rng = s.rng;
+				self.death = None;
+				Some(s.command)
+			},
+			None => None,
+		}
+	}
+
+	/*
+	Writes the game to a save file at path
+	*/
+	pub fn save(&self, path: &str) -> Result<(), String> {
+		let mut w = SaveWriter::new();
+		w.string("world", &self.world_id);
+		w.value("turns", self.turns);
+		for flag in &self.flags {
+			w.string("flag", flag);
+		}
+		for id in &self.started {
+			w.string("started", id);
+		}
+		for timer in &self.timers {
+			w.begin("timer");
+			w.string("event", &timer.event);
+			w.value("due", timer.due);
+			w.value("every", timer.every.unwrap_or(0));
+			w.end();
+		}
+		w.value("score", self.score);
+		for milestone in &self.milestones {
+			match *milestone {
+				Milestone::TAKEN(id) => w.value("taken", id),
+				Milestone::DESTROYED(id) => w.value("destroyed", id),
+				Milestone::VISITED(l) => w.value("visited", l),
+			}
+		}
+		self.rng.save(&mut w);
+		w.value("player_location", self.player.location);
+		save_items(&mut w, "inventory", &self.player.inventory);
+		w.value("location_count", self.locations.len());
+		for location in &self.locations {
+			location.save(&mut w);
+		}
+		match fs::write(path, w.finish()) {
+			Ok(_) => Ok(()),
+			Err(e) => Err(format!("Could not write {}: {}", path, e)),
+		}
+	}
+
+	/*
+	Describes the position the game is in, so that two games in the same
+	position have the same key. Used by the solver.
+	*/
+	pub fn state_key(&self) -> StateKey {
+		let mut flags: Vec<String> = self.flags.iter().cloned().collect();
+		flags.sort();
+		let mut started: Vec<String> = self.started.iter().cloned().collect();
+		started.sort();
+		let mut milestones: Vec<Milestone> = self.milestones.iter().cloned().collect();
+		milestones.sort();
+		StateKey {
+			turns: self.turn_limit().map(|t| self.turns.min(t)),
+			flags,
+			started,
+			timers: self.timers.iter()
+				.map(|t| (t.event.clone(), t.due.saturating_sub(self.turns), t.every))
+				.collect(),
+			score: self.score,
+			milestones,
+			rng: self.rng.clone(),
+			player_location: self.player.location,
+			inventory: self.player.inventory.iter().map(|i| i.key()).collect(),
+			locations: self.locations.iter().map(|l| l.key()).collect(),
+		}
+	}
+
+	/*
+	Returns the most turns any ending or event waits for, or None if
+	none of them count turns
+	*/
+	fn turn_limit(&self) -> Option<u32> {
+		self.endings.iter().flat_map(|e| e.conditions.iter())
+			.chain(self.events.iter().flat_map(|e| e.conditions.iter()))
+			.filter_map(|c| match *c {
+				Condition::TURNS(t) => Some(t),
+				_ => None,
+			})
+			.max()
+	}
+
+	/*
+	Replaces the game with the one in the save file at path. The game is
+	left as it was if the file can't be read.
+	*/
+	pub fn restore(&mut self, path: &str) -> Result<(), String> {
+		let text = match fs::read_to_string(path) {
+			Ok(t) => t,
+			Err(e) => return Err(format!("Could not read {}: {}", path, e)),
+		};
+		let mut r = SaveReader::new(&text)?;
+		if r.string("world")? != self.world_id {
+			return Err("The save file is from a different world.".to_string());
+		}
+		let turns: u32 = r.parse("turns")?;
+		let mut flags = HashSet::new();
+		while r.peek_key() == Some("flag") {
+			flags.insert(r.string("flag")?);
+		}
+		let mut started = HashSet::new();
+		while r.peek_key() == Some("started") {
+			started.insert(r.string("started")?);
+		}
+		let mut timers = Vec::new();
+		while r.peek_key() == Some("begin") {
+			r.begin("timer")?;
+			let event = r.string("event")?;
+			if !self.events.iter().any(|e| e.id == event) {
+				return Err(format!("The save file has a timer for '{}', but there is no event with that name.", event));
+			}
+			let due: u32 = r.parse("due")?;
+			let every: u32 = r.parse("every")?;
+			r.end()?;
+			timers.push(Timer {
+				event,
+				due,
+				every: if every == 0 { None } else { Some(every) },
+			});
+		}
+		let score: i32 = r.parse("score")?;
+		let mut milestones = HashSet::new();
+		loop {
+			match r.peek_key() {
+				Some("taken") => milestones.insert(Milestone::TAKEN(r.parse("taken")?)),
+				Some("destroyed") => milestones.insert(Milestone::DESTROYED(r.parse("destroyed")?)),
+				Some("visited") => milestones.insert(Milestone::VISITED(r.parse("visited")?)),
+				_ => break,
+			};
+		}
+		let rng = Rng::load(&mut r)?;
+		let player_location: usize = r.parse("player_location")?;
+		let inventory = load_items(&mut r, "inventory")?;
+		let location_count: usize = r.parse("location_count")?;
+		if player_location >= location_count {
+			return Err(format!("The save file is damaged: the player is in location {}, but there are only {}.", player_location, location_count));
+		}
+		let mut locations = Vec::new();
+		for _ in 0..location_count {
+			locations.push(Location::load(&mut r, location_count)?);
 		}
+		r.finish()?;
+
+		let mut lexicon = Lexicon::from_locations(&locations);
+		for item in &inventory {
+			for i in item.all_items() {
+				lexicon.add_item(i);
+			}
+		}
+		self.player.location = player_location;
+		self.player.inventory = inventory;
+		self.locations = locations;
+		self.lexicon = lexicon;
+		self.turns = turns;
+		self.flags = flags;
+		self.timers = timers;
+		self.started = started;
+		self.score = score;
+		self.milestones = milestones;
+		self.rng = rng;
+		self.death = None;
+		self.history.clear();
+		Ok(())
 	}
 
 	/*
@@ -40,17 +431,255 @@ impl GameState {
 		display
 	}
 
-	pub fn update_player(&self) -> bool {
-		let idol = ItemNode {
-			subject: "idol".to_string(),
-			subject_lexeme: "idol".to_string(),
+	/*
+	Starts any events whose conditions now hold, then fires every event
+	that is due this turn. Each event fires at most once a turn, so
+	events that start each other can't loop forever. An event with a
+	chance of firing rolls the game's rng each time it is due.
+	*/
+	pub fn run_events(&mut self) -> String {
+		let mut display = String::new();
+		let ready: Vec<String> = self.events.iter()
+			.filter(|e| !self.started.contains(&e.id) && e.is_ready(self))
+			.map(|e| e.id.clone())
+			.collect();
+		for id in ready {
+			self.start_event(&id);
+		}
+
+		let mut fired: HashSet<String> = HashSet::new();
+		while let Some(i) = self.timers.iter().position(|t| t.due <= self.turns && !fired.contains(&t.event)) {
+			let timer = self.timers.remove(i);
+			if let Some(every) = timer.every {
+				self.timers.push(Timer {
+					event: timer.event.clone(),
+					due: self.turns + every,
+					every: Some(every),
+				});
+			}
+			fired.insert(timer.event.clone());
+			let chance = self.events.iter().find(|e| e.id == timer.event).map_or(100, |e| e.chance);
+			if chance >= 100 || self.rng.chance(chance) {
+				self.fire_event(&timer.event, &mut display);
+			}
+		}
+		display
+	}
+
+	/*
+	Shows an event's text and carries out its effects
+	*/
+	fn fire_event(&mut self, id: &str, display: &mut String) {
+		let event = match self.events.iter().find(|e| e.id == id) {
+			Some(e) => e.clone(),
+			None => return,
 		};
-		match self.player.find_item(&idol) {
-			Some(_) => true,
+		if !event.text.is_empty() {
+			display.push_str(&event.text);
+			display.push('\n');
+		}
+		for effect in event.effects {
+			match effect {
+				Effect::FLAG(f) => self.set_flag(&f),
+				Effect::DESTROY(i) => {
+					let item = match self.player.find_item_by_id_mut(i) {
+						Some(item) => Some(item),
+						None => self.locations.iter_mut().filter_map(|l| l.find_item_by_id_mut(i)).next(),
+					};
+					if let Some(item) = item {
+						item.destroy();
+					}
+				},
+				Effect::DESCRIBE(l, d) => if let Some(location) = self.locations.get_mut(l) {
+					location.desc = d;
+				},
+				Effect::START(e) => { self.start_event(&e); },
+				Effect::CANCEL(e) => { self.cancel_event(&e); },
+			}
+		}
+	}
+
+	/*
+	Starts the event with its own timing, restarting it if it is
+	already waiting. Returns false if there is no such event.
+	*/
+	pub fn start_event(&mut self, id: &str) -> bool {
+		match self.events.iter().find(|e| e.id == id).map(|e| e.timing) {
+			Some(Timing::FUSE(turns)) => self.schedule_fuse(id, turns),
+			Some(Timing::DAEMON(every)) => self.schedule_daemon(id, every),
 			None => false,
 		}
 	}
 
+	/*
+	Fires the event once, the given number of turns from now
+	*/
+	pub fn schedule_fuse(&mut self, id: &str, turns: u32) -> bool {
+		self.schedule(id, turns, None)
+	}
+
+	/*
+	Fires the event every so many turns, starting that many turns from
+	now, until it is cancelled
+	*/
+	pub fn schedule_daemon(&mut self, id: &str, every: u32) -> bool {
+		self.schedule(id, every.max(1), Some(every.max(1)))
+	}
+
+	fn schedule(&mut self, id: &str, turns: u32, every: Option<u32>) -> bool {
+		if !self.events.iter().any(|e| e.id == id) {
+			return false;
+		}
+		self.cancel_event(id);
+		self.started.insert(id.to_string());
+		self.timers.push(Timer {
+			event: id.to_string(),
+			due: self.turns + turns,
+			every,
+		});
+		true
+	}
+
+	/*
+	Stops the event from firing. Returns false if it wasn't waiting.
+	*/
+	pub fn cancel_event(&mut self, id: &str) -> bool {
+		let count = self.timers.len();
+		self.timers.retain(|t| t.event != id);
+		self.timers.len() != count
+	}
+
+	/*
+	Returns true if the event is waiting to fire
+	*/
+	pub fn is_scheduled(&self, id: &str) -> bool {
+		self.timers.iter().any(|t| t.event == id)
+	}
+
+	/*
+	Awards the points for every milestone reached that hasn't already
+	scored: entering the current location, carrying items and destroyed
+	items
+	*/
+	pub fn check_score(&mut self) -> String {
+		let mut reached: Vec<(Milestone, i32)> = Vec::new();
+		let l = self.player.location;
+		if self.locations[l].points != 0 {
+			reached.push((Milestone::VISITED(l), self.locations[l].points));
+		}
+		for item in self.player.inventory.iter().flat_map(|i| i.all_items()) {
+			if item.take_points != 0 {
+				reached.push((Milestone::TAKEN(item.get_id()), item.take_points));
+			}
+		}
+		let all_items = self.player.inventory.iter()
+			.flat_map(|i| i.all_items())
+			.chain(self.locations.iter().flat_map(|l| l.all_items()));
+		for item in all_items {
+			if item.destroy_points != 0 && item.damage_level() == DamageLevel::DESTROYED {
+				reached.push((Milestone::DESTROYED(item.get_id()), item.destroy_points));
+			}
+		}
+
+		let mut gained = 0;
+		for (milestone, points) in reached {
+			if self.milestones.insert(milestone) {
+				gained += points;
+			}
+		}
+		self.score += gained;
+		match gained {
+			0 => String::new(),
+			1 => "[Your score has gone up by 1 point.]".to_string(),
+			n if n > 0 => format!("[Your score has gone up by {} points.]", n),
+			n => format!("[Your score has gone down by {} point{}.]", -n, if n == -1 { "" } else { "s" }),
+		}
+	}
+
+	/*
+	Describes the score so far and the rank it earns
+	*/
+	pub fn describe_score(&self) -> String {
+		score::describe(self.score, self.max_score, self.turns)
+	}
+
+	pub fn score(&self) -> i32 {
+		self.score
+	}
+
+	pub fn max_score(&self) -> i32 {
+		self.max_score
+	}
+
+	/*
+	Returns the first ending whose conditions hold, if any
+	*/
+	pub fn check_endings(&self) -> Option<&Ending> {
+		self.endings.iter().find(|e| e.is_reached(self))
+	}
+
+	pub fn endings(&self) -> &[Ending] {
+		&self.endings
+	}
+
+	/*
+	Checks the hazards with the given trigger, killing the player with
+	the first one that fires. Returns true if the player died.
+	*/
+	pub fn check_hazards(&mut self, trigger: Trigger) -> bool {
+		if self.death.is_none() {
+			self.death = self.hazards.iter().find(|h| h.fires(&trigger, self)).map(|h| h.ending.clone());
+		}
+		self.death.is_some()
+	}
+
+	/*
+	Returns how the player died, if they have
+	*/
+	pub fn death(&self) -> Option<&Ending> {
+		self.death.as_ref()
+	}
+
+	/*
+	Counts a turn as taken
+	*/
+	pub fn next_turn(&mut self) {
+		self.turns += 1;
+	}
+
+	pub fn turns(&self) -> u32 {
+		self.turns
+	}
+
+	pub fn set_flag(&mut self, flag: &str) {
+		self.flags.insert(flag.to_string());
+	}
+
+	pub fn has_flag(&self, flag: &str) -> bool {
+		self.flags.contains(flag)
+	}
+
+	/*
+	Finds an item anywhere in the world, carried or not
+	*/
+	pub fn find_item_by_id(&self, id: Uuid) -> Option<Item> {
+		match self.player.find_item_by_id(id) {
+			Some(i) => Some(i),
+			None => self.locations.iter().filter_map(|l| l.find_item_by_id(id)).next(),
+		}
+	}
+
+	/*
+	Marks the player's location as visited once they can see it, so its
+	first visit text is only shown the first time it is described
+	*/
+	pub fn mark_visited(&mut self) {
+		if self.can_see() {
+			let loc = self.player.location;
+			self.locations[loc].visited = true;
+		}
+	}
+
 	/*
 	Returns a pointer to the player's current location
 	*/
@@ -58,6 +687,43 @@ impl GameState {
 		&self.locations[self.player.location]
 	}
 
+	/*
+	Formats a location or item as the player sees it now, so that its
+	description can depend on the turn, the flags, and what is around
+	(see template.rs)
+	*/
+	pub fn render<T: fmt::Display>(&self, thing: &T) -> String {
+		let context = Context {
+			turns: self.turns,
+			flags: self.flags.clone(),
+			first: !self.current_location().visited,
+			here: self.current_location().all_items().into_iter().map(Seen::new).collect(),
+			held: self.player.inventory.iter().flat_map(|i| i.all_items()).map(Seen::new).collect(),
+		};
+		template::with_context(context, || thing.to_string())
+	}
+
+	/*
+	Returns the text shown before the game begins, which may be empty
+	*/
+	pub fn intro(&self) -> &str {
+		&self.initial.intro
+	}
+
+	/*
+	Returns a pointer to the world's item vocabulary
+	*/
+	pub fn lexicon(&self) -> &Lexicon {
+		&self.lexicon
+	}
+
+	/*
+	Returns the locations of the world, in order
+	*/
+	pub fn locations(&self) -> &[Location] {
+		&self.locations
+	}
+
 	/*
 	Returns a pointer to the player
 	*/
@@ -71,11 +737,49 @@ impl GameState {
 	*/
 	pub fn move_player(&mut self, d: DirectionType) -> Option<usize> {
 		match self.locations[self.player.location].find_exit(d) {
-			Some(e) => {
+			Some(e) if e < self.locations.len() => {
 				let old_loc = self.player.location;
 				self.player.location = e;
+				self.check_hazards(Trigger::ENTER(e));
 				Some(old_loc)
 			},
+			_ => None,
+		}
+	}
+
+	/*
+	Returns true if the player can see: the location isn't dark, or a
+	light source is carried or present
+	*/
+	pub fn can_see(&self) -> bool {
+		!self.current_location().is_dark
+			|| self.player.inventory.iter().any(|i| i.has_light())
+			|| self.current_location().has_light()
+	}
+
+	/*
+	Burns a turn of fuel from every light source that is on. Only light
+	sources the player can see report that they are running low.
+	*/
+	pub fn burn_fuel(&mut self) -> String {
+		let mut display = String::new();
+		for item in &mut self.player.inventory {
+			item.burn_fuel(&mut display, true);
+		}
+		let here = self.player.location;
+		for (l, location) in self.locations.iter_mut().enumerate() {
+			location.burn_fuel(&mut display, l == here);
+		}
+		display
+	}
+
+	/*
+	Turns an item the player can reach on or off. Returns None if there
+	is no such item or it can't be turned on, otherwise whether it worked.
+	*/
+	pub fn set_item_on(&mut self, i_node: &ItemNode, b: bool) -> Option<bool> {
+		match self.find_item_mut(i_node) {
+			Some(item) => item.set_on(b),
 			None => None,
 		}
 	}
@@ -87,31 +791,133 @@ impl GameState {
 		}
 	}
 
+	/*
+	Returns a mutable reference to an item the player can reach, checking
+	the inventory before the current location
+	*/
+	fn find_item_mut(&mut self, i_node: &ItemNode) -> Option<&mut Item> {
+		let loc = self.player.location;
+		match self.player.find_item_mut(i_node) {
+			Some(i) => Some(i),
+			None => self.locations[loc].find_item_mut(i_node),
+		}
+	}
+
 	/*
 	Attempts to move an item from the current location to the player's
-	inventory. If the item is not fixed, it succeeds. The Location.remove_item()
-	method does not remove it from it's list if the item is fixed.
+	inventory. If the item is not fixed or scenery, it succeeds. Fixed
+	items and scenery are returned but left where they are.
 	*/
 	pub fn get_item(&mut self, i_node: &ItemNode) -> Option<Item> {
-		match self.locations[self.player.location].remove_item(&i_node) {
-			Some(i) => {
-				if !i.is_fixed {
+		match self.locations[self.player.location].find_item(i_node) {
+			Some(ref i) if i.is_fixed || i.is_scenery => Some(i.clone()),
+			Some(_) => match self.locations[self.player.location].remove_item(i_node) {
+				Some(i) => {
 					self.player.add_item(i.clone());
-				} else {
-					self.locations[self.player.location].add_item(i.clone());
-				}
+					Some(i)
+				},
+				None => None,
+			},
+			None => None,
+		}
+	}
+
+	/*
+	Attempts to move an item from inside/on the container matching c_node
+	to the player's inventory. Works like get_item otherwise.
+	*/
+	pub fn get_item_from(&mut self, i_node: &ItemNode, c_node: &ItemNode) -> Option<Item> {
+		let item = match self.find_item_mut(c_node) {
+			Some(container) => match container.find_item(i_node) {
+				Some(ref i) if i.is_fixed || i.is_scenery => return Some(i.clone()),
+				Some(_) => container.remove_item(i_node),
+				None => None,
+			},
+			None => None,
+		};
+		match item {
+			Some(i) => {
+				self.player.add_item(i.clone());
 				Some(i)
 			},
 			None => None,
 		}
 	}
 
+	/*
+	Attempts to move an item from the player's inventory in/on/under/behind
+	the item matching c_node. If it can't be done, the item stays in the
+	inventory and false is returned.
+	*/
+	pub fn put_item(&mut self, i_node: &ItemNode, p: PrepositionType, c_node: &ItemNode) -> bool {
+		let item = match self.player.remove_item(i_node) {
+			Some(i) => i,
+			None => return false,
+		};
+		let handed_back = match self.find_item_mut(c_node) {
+			Some(container) => container.put_item(p, item),
+			None => Some(item),
+		};
+		match handed_back {
+			Some(item) => {
+				self.player.add_item(item);
+				false
+			},
+			None => true,
+		}
+	}
+
+	/*
+	Moves the items hidden under or behind the item matching i_node into
+	the current location, where they can be seen and taken. Returns None
+	if the item can't be found.
+	*/
+	pub fn reveal_items(&mut self, i_node: &ItemNode, p: PrepositionType) -> Option<Vec<Item>> {
+		let found = match self.find_item_mut(i_node) {
+			Some(item) => item.take_hidden_items(p),
+			None => return None,
+		};
+		for item in &found {
+			self.locations[self.player.location].add_item(item.clone());
+		}
+		Some(found)
+	}
+
+	/*
+	Returns a copy of the door matching i_node in the current location's
+	exits, if there is one
+	*/
+	pub fn find_door(&self, i_node: &ItemNode) -> Option<Door> {
+		self.current_location().find_door(i_node)
+	}
+
+	/*
+	Changes every copy of the door to match door, so both sides of it
+	agree
+	*/
+	pub fn update_door(&mut self, door: &Door) {
+		for location in &mut self.locations {
+			location.update_door(door);
+		}
+	}
+
+	/*
+	Opens or closes the item matching i_node. Returns None if it can't be
+	found or opened, otherwise whether the open state changed.
+	*/
+	pub fn open_item(&mut self, i_node: &ItemNode, b: bool) -> Option<bool> {
+		match self.find_item_mut(i_node) {
+			Some(item) => item.set_open(b),
+			None => None,
+		}
+	}
+
 	/*
 	Attempts to move an item from the player's inventory to the current
 	location.
 	*/
 	pub fn drop_item(&mut self, i_node: &ItemNode) -> Option<Item> {
-		match self.player.remove_item(&i_node) {
+		match self.player.remove_item(i_node) {
 			Some(i) => {
 				self.locations[self.player.location].add_item(i.clone());
 				Some(i)
@@ -121,58 +927,74 @@ impl GameState {
 	}
 
 	/*
-	
+	Damages the item in the current location matching i_node, if it can
+	be damaged by damage_type. The item is damaged where it is, so it
+	stays on or in whatever holds it. Returns the damaged item if
+	successful.
+
 	todo: add damage_amount as a third parameter
 	*/
-	pub fn damage_first_item(&mut self, damage_type: Option<DamageType>) -> Option<Item> {
-		match damage_type {
-			Some(dt) => match self.locations[self.player.location].remove_first_item_by_damage_type(dt) {
-				Some(mut item) => {
-					item.damage(1);
-					self.locations[self.player.location].add_item(item.clone());
-					Some(item)
-				},
-				None => None,
-			},
-			None => None,
-		}
+	pub fn damage_item(&mut self, i_node: &ItemNode, damage_type: DamageType) -> Option<Item> {
+		let loc = self.player.location;
+		damage(self.locations[loc].find_item_mut(i_node), damage_type)
 	}
 
 	/*
+	Damages the item in the current location with the given id, the same
+	way as damage_item
+	*/
+	pub fn damage_item_by_id(&mut self, id: Uuid, damage_type: DamageType) -> Option<Item> {
+		let loc = self.player.location;
+		damage(self.locations[loc].find_item_by_id_mut(id), damage_type)
+	}
 
+	/*
+	Attaches the item in the player's inventory matching sub_node to the
+	item in the current location matching obj_node. Returns the item it
+	was attached to if successful.
 	*/
-	pub fn attach_first_item(&mut self, i_node: &ItemNode, sub_item: Item) -> Option<Item> {
-		if sub_item.can_attach {
-			match self.locations[self.player.location].remove_first_attachable_item() {
-				Some(mut item) => {
-					item.attach_item(sub_item);
-					self.locations[self.player.location].add_item(item.clone());
-					self.player.remove_item(i_node);
-					Some(item)
-				},
-				None => None,
-			}
-		} else {
-			None
+	pub fn attach_item(&mut self, sub_node: &ItemNode, obj_node: &ItemNode) -> Option<Item> {
+		match self.player.find_item(sub_node) {
+			Some(ref sub_item) if sub_item.can_attach => {
+				match self.locations[self.player.location].remove_attachable_item(obj_node) {
+					Some(mut item) => {
+						if let Some(sub_item) = self.player.remove_item(sub_node) {
+							item.attach_item(sub_item);
+						}
+						self.locations[self.player.location].add_item(item.clone());
+						Some(item)
+					},
+					None => None,
+				}
+			},
+			_ => None,
 		}
 	}
+}
 
+/*
+Damages item by one, if it can be damaged by damage_type, and returns a
+copy of it
+*/
+fn damage(item: Option<&mut Item>, damage_type: DamageType) -> Option<Item> {
+	match item {
+		Some(item) if item.damaged_by == Some(damage_type) => {
+			item.damage(1);
+			Some(item.clone())
+		},
+		_ => None,
+	}
+}
 
-	pub fn turn_on_item(&mut self, i_node: &ItemNode) -> Option<bool> {
-		match self.player.remove_item(&i_node) {
-			Some(mut item) => {
-				let result = item.toggle_on();
-				self.player.add_item(item);
-				result
-			},
-			None => match self.locations[self.player.location].remove_item(&i_node) {
-				Some(mut item) => {
-					let result = item.toggle_on();
-					self.locations[self.player.location].add_item(item);
-					result
-				},
-				None => None,
-			},
-		}
+/*
+Names the world a game is played in, so that a game saved in one world
+isn't restored into another. It is a hash of the world's locations and
+items as they are when the game begins.
+*/
+fn world_id(locations: &[Location]) -> String {
+	let mut w = SaveWriter::new();
+	for location in locations {
+		location.save(&mut w);
 	}
-}
\ No newline at end of file
+	format!("{:016x}", fingerprint(&w.finish()))
+}
@@ -0,0 +1,45 @@
+/*
+Hazards, the ways the player can die.
+
+A hazard is a fatal Ending with a trigger. Jump hazards fire when the
+player jumps into or across an item, enter hazards when the player walks
+into a location, and turn hazards are checked after every turn. In every
+case the hazard's conditions must also hold.
+*/
+
+use uuid::Uuid;
+
+use game::ending::Ending;
+use game::gamestate::GameState;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trigger {
+	JUMP(Uuid),
+	ENTER(usize),
+	TURN,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hazard {
+	pub trigger: Trigger,
+	pub ending: Ending,
+}
+
+impl Hazard {
+	/*
+	Creates a Hazard that ends the game with ending when triggered.
+	*/
+	pub fn new(trigger: Trigger, ending: Ending) -> Hazard {
+		Hazard {
+			trigger,
+			ending,
+		}
+	}
+
+	/*
+	Returns true if the trigger matches and the conditions hold
+	*/
+	pub fn fires(&self, trigger: &Trigger, game_state: &GameState) -> bool {
+		self.trigger == *trigger && self.ending.conditions_hold(game_state)
+	}
+}
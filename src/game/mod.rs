@@ -1,2 +1,11 @@
+pub mod ending;
+pub mod engine;
+pub mod event;
 pub mod gamecontroller;
-pub mod gamestate;
\ No newline at end of file
+pub mod gamestate;
+pub mod hazard;
+pub mod rng;
+pub mod save;
+pub mod score;
+pub mod solver;
+pub mod walkthrough;
\ No newline at end of file
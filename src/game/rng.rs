@@ -0,0 +1,122 @@
+/*
+A small seeded random number generator.
+
+Everything random in RustADE comes from an Rng, so the same seed always
+gives the same results, on every platform and in every version. It is
+SplitMix64: one u64 of state, which makes it cheap to copy and easy to
+save. The seed it started from is kept, so it can be shown to the
+player.
+
+A game has one Rng, owned by its GameState. A generated world is built
+with it before the game begins, and events use it as the game goes on.
+*/
+
+use uuid::Uuid;
+
+use game::save::{SaveWriter,SaveReader};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rng {
+	seed: u64,
+	state: u64,
+}
+
+impl Rng {
+	/*
+	Creates an Rng. Rngs made with the same seed produce the same
+	numbers.
+	*/
+	pub fn new(seed: u64) -> Rng {
+		Rng {
+			seed,
+			state: seed,
+		}
+	}
+
+	/*
+	Returns the seed the Rng was created with
+	*/
+	pub fn seed(&self) -> u64 {
+		self.seed
+	}
+
+	/*
+	Returns the next random number
+	*/
+	pub fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+
+	/*
+	Returns a number from 0 up to, but not including, n. n must not be
+	zero.
+	*/
+	pub fn below(&mut self, n: usize) -> usize {
+		(self.next_u64() % n as u64) as usize
+	}
+
+	/*
+	Returns a number from low to high, including both
+	*/
+	pub fn between(&mut self, low: usize, high: usize) -> usize {
+		low + self.below(high - low + 1)
+	}
+
+	/*
+	Returns true percent times out of a hundred
+	*/
+	pub fn chance(&mut self, percent: u32) -> bool {
+		self.below(100) < percent as usize
+	}
+
+	/*
+	Picks one of items, which must not be empty
+	*/
+	pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+		&items[self.below(items.len())]
+	}
+
+	/*
+	Puts items in a random order
+	*/
+	pub fn shuffle<T>(&mut self, items: &mut [T]) {
+		for i in (1..items.len()).rev() {
+			let j = self.below(i + 1);
+			items.swap(i, j);
+		}
+	}
+
+	/*
+	Returns a random id, shaped like a version 4 Uuid
+	*/
+	pub fn id(&mut self) -> Uuid {
+		let mut bytes = [0u8; 16];
+		bytes[..8].copy_from_slice(&self.next_u64().to_le_bytes());
+		bytes[8..].copy_from_slice(&self.next_u64().to_le_bytes());
+		bytes[6] = (bytes[6] & 0x0f) | 0x40;
+		bytes[8] = (bytes[8] & 0x3f) | 0x80;
+		Uuid::from_bytes(&bytes).unwrap() // Always 16 bytes
+	}
+
+	/*
+	Writes the seed and where the Rng has got to in a save file
+	*/
+	pub fn save(&self, w: &mut SaveWriter) {
+		w.value("seed", self.seed);
+		w.value("rng", self.state);
+	}
+
+	/*
+	Reads an Rng written by save
+	*/
+	pub fn load(r: &mut SaveReader) -> Result<Rng, String> {
+		Ok(Rng {
+			seed: r.parse("seed")?,
+			state: r.parse("rng")?,
+		})
+	}
+}
@@ -0,0 +1,216 @@
+/*
+Reading and writing saved games.
+
+A save file is plain text, one value per line:
+	key value
+Nested things are wrapped in blocks:
+	begin item
+	...
+	end
+Values are written and read back in the same order, so each type's save
+code lives next to the type (see GameState, Location and Item). The first
+line holds the format version, which is checked before anything else is
+read. The GameState follows it with the world the game was played in.
+*/
+
+use std::fmt::{Display,Write};
+use std::str::FromStr;
+
+use obj::DamageType;
+
+pub const SAVE_VERSION: u32 = 9;
+const SAVE_HEADER: &str = "RustADE save";
+
+#[derive(Debug)]
+pub struct SaveWriter {
+	out: String,
+}
+
+impl Default for SaveWriter {
+	fn default() -> SaveWriter {
+		SaveWriter::new()
+	}
+}
+
+impl SaveWriter {
+	/*
+	Creates a new SaveWriter, starting with the version header.
+	*/
+	pub fn new() -> SaveWriter {
+		let mut writer = SaveWriter {
+			out: String::new(),
+		};
+		writer.value(SAVE_HEADER, SAVE_VERSION);
+		writer
+	}
+
+	pub fn begin(&mut self, block: &str) {
+		self.value("begin", block);
+	}
+
+	pub fn end(&mut self) {
+		self.out.push_str("end\n");
+	}
+
+	pub fn value<T: Display>(&mut self, key: &str, v: T) {
+		let _ = writeln!(self.out, "{} {}", key, v);
+	}
+
+	/*
+	Writes a string, escaping backslashes and new lines so it stays on
+	one line.
+	*/
+	pub fn string(&mut self, key: &str, s: &str) {
+		self.value(key, s.replace('\\', "\\\\").replace('\n', "\\n"));
+	}
+
+	pub fn damage_type(&mut self, key: &str, d: &Option<DamageType>) {
+		self.value(key, match *d {
+			Some(DamageType::SMASHING) => "smashing",
+			Some(DamageType::CUTTING) => "cutting",
+			None => "none",
+		});
+	}
+
+	pub fn finish(self) -> String {
+		self.out
+	}
+}
+
+#[derive(Debug)]
+pub struct SaveReader<'a> {
+	lines: Vec<&'a str>,
+	pos: usize,
+}
+
+impl<'a> SaveReader<'a> {
+	/*
+	Creates a new SaveReader over the text of a save file, checking the
+	version header first.
+	*/
+	pub fn new(text: &'a str) -> Result<SaveReader<'a>, String> {
+		let mut reader = SaveReader {
+			lines: text.lines().collect(),
+			pos: 0,
+		};
+		let header = match reader.lines.first() {
+			Some(l) => *l,
+			None => return Err("The save file is empty.".to_string()),
+		};
+		if !header.starts_with(SAVE_HEADER) {
+			return Err("This is not a RustADE save file.".to_string());
+		}
+		let version: u32 = reader.parse(SAVE_HEADER)?;
+		if version != SAVE_VERSION {
+			return Err(format!("The save file uses version {} of the save format, but this game reads version {}.", version, SAVE_VERSION));
+		}
+		Ok(reader)
+	}
+
+	/*
+	Returns the key of the next line without reading it
+	*/
+	pub fn peek_key(&self) -> Option<&'a str> {
+		self.lines.get(self.pos).map(|l| split_line(l).0)
+	}
+
+	/*
+	Reads the next line, which must have the given key, and returns its
+	raw value
+	*/
+	fn next(&mut self, key: &str) -> Result<&'a str, String> {
+		let line = match self.lines.get(self.pos) {
+			Some(l) => *l,
+			None => return Err(format!("The save file ends early, expected '{}'.", key)),
+		};
+		self.pos += 1;
+		let (k, v) = split_line(line);
+		if k == key {
+			Ok(v)
+		} else {
+			Err(format!("The save file is damaged: expected '{}' on line {}, found '{}'.", key, self.pos, k))
+		}
+	}
+
+	pub fn begin(&mut self, block: &str) -> Result<(), String> {
+		let line = self.pos + 1;
+		match self.next("begin")? {
+			b if b == block => Ok(()),
+			b => Err(format!("The save file is damaged: expected a {} on line {}, found a {}.", block, line, b)),
+		}
+	}
+
+	pub fn end(&mut self) -> Result<(), String> {
+		self.next("end").map(|_| ())
+	}
+
+	pub fn string(&mut self, key: &str) -> Result<String, String> {
+		let raw = self.next(key)?;
+		let mut s = String::new();
+		let mut chars = raw.chars();
+		while let Some(c) = chars.next() {
+			if c == '\\' {
+				match chars.next() {
+					Some('n') => s.push('\n'),
+					Some(other) => s.push(other),
+					None => s.push('\\'),
+				}
+			} else {
+				s.push(c);
+			}
+		}
+		Ok(s)
+	}
+
+	pub fn parse<T: FromStr>(&mut self, key: &str) -> Result<T, String> {
+		let line = self.pos + 1;
+		let raw = self.next(key)?;
+		match raw.parse::<T>() {
+			Ok(v) => Ok(v),
+			Err(_) => Err(format!("The save file is damaged: '{}' on line {} is not valid.", raw, line)),
+		}
+	}
+
+	pub fn damage_type(&mut self, key: &str) -> Result<Option<DamageType>, String> {
+		let line = self.pos + 1;
+		match self.next(key)? {
+			"smashing" => Ok(Some(DamageType::SMASHING)),
+			"cutting" => Ok(Some(DamageType::CUTTING)),
+			"none" => Ok(None),
+			raw => Err(format!("The save file is damaged: '{}' on line {} is not a damage type.", raw, line)),
+		}
+	}
+
+	/*
+	Makes sure nothing is left over after the game has been read
+	*/
+	pub fn finish(&self) -> Result<(), String> {
+		if self.pos < self.lines.len() {
+			Err(format!("The save file is damaged: unexpected data on line {}.", self.pos + 1))
+		} else {
+			Ok(())
+		}
+	}
+}
+
+/*
+Hashes text with FNV-1a. Unlike the standard hasher, it gives the same
+hash in every run, so the hash can be kept in a save file.
+*/
+pub fn fingerprint(text: &str) -> u64 {
+	text.bytes().fold(0xcbf2_9ce4_8422_2325, |h, b| (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/*
+Splits a line into its key and value. The key is everything before the
+first space, except on the header line, where it is the whole header.
+*/
+fn split_line(line: &str) -> (&str, &str) {
+	if let Some(rest) = line.strip_prefix(SAVE_HEADER) {
+		return (SAVE_HEADER, rest.trim_start());
+	}
+	match line.find(' ') {
+		Some(i) => (&line[..i], &line[i + 1..]),
+		None => (line, ""),
+	}
+}
@@ -0,0 +1,57 @@
+/*
+Scoring.
+
+Points are attached to the world itself: an item can be worth points the
+first time it is taken (take_points) or when it is destroyed
+(destroy_points), and a location the first time the player enters it
+(points). Each milestone only scores once. The maximum score is every
+milestone in the world added up, leaving out any penalties (negative
+points).
+*/
+
+use uuid::Uuid;
+
+/*
+Something the player has done that scored points
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Milestone {
+	TAKEN(Uuid),
+	DESTROYED(Uuid),
+	VISITED(usize),
+}
+
+/*
+Rank titles, with the share of the maximum score (in percent) needed for
+each
+*/
+const RANKS: [(i32, &str); 6] = [
+	(100, "Master Adventurer"),
+	(75, "Seasoned Explorer"),
+	(50, "Adventurer"),
+	(25, "Novice Adventurer"),
+	(1, "Amateur"),
+	(0, "Beginner"),
+];
+
+/*
+Returns the rank title for a score out of max
+*/
+pub fn rank(score: i32, max: i32) -> &'static str {
+	let percent = if max > 0 { score * 100 / max } else { 0 };
+	match RANKS.iter().find(|r| percent >= r.0) {
+		Some(r) => r.1,
+		None => RANKS[RANKS.len() - 1].1,
+	}
+}
+
+/*
+Describes the score and rank, as shown by the SCORE command and at the
+end of the game
+*/
+pub fn describe(score: i32, max: i32, turns: u32) -> String {
+	format!("You have scored {} of {} point{} in {} turn{}.\nThat gives you the rank of {}.",
+		score, max, if max == 1 { "" } else { "s" },
+		turns, if turns == 1 { "" } else { "s" },
+		rank(score, max))
+}
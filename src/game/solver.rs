@@ -0,0 +1,299 @@
+/*
+Checks that a world can be won.
+
+The solver plays the world the way a very patient player would. From the
+start it tries every command that might make progress, then every
+command from each position that leads to, breadth first, so the first
+win it finds is the shortest. Positions are compared with
+GameState::state_key, so reaching the same place by another route isn't
+explored twice.
+
+The commands tried are: going through each exit, taking, opening,
+turning on, looking under and behind, cutting or smashing things with a
+carried tool of the right kind, tying carried things to other things,
+and unlocking doors with a carried key. Nothing is ever dropped, put
+down, closed, locked or turned off, so a world that can only be won by
+doing one of those is reported as unwinnable.
+
+A dead end is a position where the game is still going but can no
+longer be won. Dead ends can only be known once every position has been
+explored, so none are reported if the search stops at its limit. Each
+one is given as the shortest way into it, and only where the player
+first gets stuck, not every position after.
+*/
+
+use std::collections::{HashMap,VecDeque};
+use std::fmt;
+
+use game::engine::{Engine,GameStatus};
+use game::gamestate::{GameState,StateKey};
+use obj::{DamageType,DamageLevel};
+use obj::item::Item;
+use world::World;
+
+pub const DEFAULT_MAX_STATES: usize = 100_000;
+
+/*
+What the solver found:
+	solution: the shortest winning commands, or None if it found no way
+	to win
+	dead_ends: the shortest way into each dead end
+	states: how many positions were explored
+	complete: false if the search stopped at its limit
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+	pub solution: Option<Vec<String>>,
+	pub dead_ends: Vec<Vec<String>>,
+	pub states: usize,
+	pub complete: bool,
+}
+
+/*
+A position the solver has reached, and how it got there
+*/
+#[derive(Debug)]
+struct Node {
+	parent: Option<usize>,
+	command: String,
+	status: GameStatus,
+	next: Vec<usize>, // The positions each command leads to
+}
+
+/*
+Searches the world for the shortest way to win, exploring at most
+max_states positions
+*/
+pub fn solve(world: World, max_states: usize) -> Report {
+	let mut game_state = GameState::from_world(world);
+	game_state.set_undo_depth(0);
+	let mut engine = Engine::new(game_state);
+	engine.start();
+
+	let mut nodes = vec![Node {
+		parent: None,
+		command: String::new(),
+		status: engine.status(),
+		next: Vec::new(),
+	}];
+	let mut seen: HashMap<(GameStatus, StateKey), usize> = HashMap::new();
+	seen.insert(position(&engine), 0);
+	let mut queue: VecDeque<(usize, Engine)> = VecDeque::new();
+	queue.push_back((0, engine));
+	let mut winner: Option<usize> = None;
+	let mut complete = true;
+
+	while let Some((n, engine)) = queue.pop_front() {
+		for command in commands(engine.game_state()) {
+			let mut next = engine.clone();
+			next.step(&command);
+			let key = position(&next);
+			let child = match seen.get(&key) {
+				Some(&c) => c,
+				None if nodes.len() >= max_states => {
+					complete = false;
+					continue;
+				},
+				None => {
+					let c = nodes.len();
+					nodes.push(Node {
+						parent: Some(n),
+						command,
+						status: next.status(),
+						next: Vec::new(),
+					});
+					seen.insert(key, c);
+					match next.status() {
+						GameStatus::WON if winner.is_none() => winner = Some(c),
+						GameStatus::PLAYING => queue.push_back((c, next)),
+						_ => (),
+					}
+					c
+				},
+			};
+			nodes[n].next.push(child);
+		}
+	}
+
+	let dead_ends = match winner {
+		Some(_) if complete => find_dead_ends(&nodes),
+		_ => Vec::new(),
+	};
+	Report {
+		solution: winner.map(|w| path(&nodes, w)),
+		dead_ends,
+		states: nodes.len(),
+		complete,
+	}
+}
+
+/*
+Identifies a position. Deaths and endings leave the world as it was, so
+the status is part of it.
+*/
+fn position(engine: &Engine) -> (GameStatus, StateKey) {
+	(engine.status(), engine.game_state().state_key())
+}
+
+/*
+Returns the commands that lead from the start to node n
+*/
+fn path(nodes: &[Node], n: usize) -> Vec<String> {
+	let mut commands = Vec::new();
+	let mut current = n;
+	while let Some(parent) = nodes[current].parent {
+		commands.push(nodes[current].command.clone());
+		current = parent;
+	}
+	commands.reverse();
+	commands
+}
+
+/*
+Finds the positions that can't lead to a win, working back from every
+winning position, and returns the way into each one whose parent could
+still win
+*/
+fn find_dead_ends(nodes: &[Node]) -> Vec<Vec<String>> {
+	let mut previous: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+	for (n, node) in nodes.iter().enumerate() {
+		for &c in &node.next {
+			previous[c].push(n);
+		}
+	}
+	let mut winnable = vec![false; nodes.len()];
+	let mut queue: VecDeque<usize> = VecDeque::new();
+	for (n, node) in nodes.iter().enumerate() {
+		if node.status == GameStatus::WON {
+			winnable[n] = true;
+			queue.push_back(n);
+		}
+	}
+	while let Some(n) = queue.pop_front() {
+		for &p in &previous[n] {
+			if !winnable[p] {
+				winnable[p] = true;
+				queue.push_back(p);
+			}
+		}
+	}
+	nodes.iter().enumerate()
+		.filter(|&(n, node)| node.status == GameStatus::PLAYING && !winnable[n])
+		.filter(|&(_, node)| node.parent.is_some_and(|p| winnable[p]))
+		.map(|(n, _)| path(nodes, n))
+		.collect()
+}
+
+/*
+Lists the commands worth trying in the game's current position
+*/
+fn commands(game_state: &GameState) -> Vec<String> {
+	let location = game_state.current_location();
+	let mut commands: Vec<String> = location.exits().iter()
+		.map(|&(d, _)| d.name().to_string())
+		.collect();
+
+	let carried: Vec<&Item> = game_state.player().inventory.iter()
+		.flat_map(|i| i.all_items())
+		.collect();
+	let mut items = location.all_items();
+	items.extend(carried.iter().cloned());
+	let nouns: Vec<(&str, Vec<String>)> = items.iter()
+		.map(|i| (i.i_type.as_str(), i.all_adjectives()))
+		.collect();
+	let noun = |item: &Item| name(&item.i_type, &item.all_adjectives(), &nouns);
+
+	for item in &items {
+		let n = noun(item);
+		let held = carried.iter().any(|c| c.get_id() == item.get_id());
+		if !held && !item.is_fixed && !item.is_scenery {
+			commands.push(format!("take {}", n));
+		}
+		if item.can_open && !item.is_open {
+			commands.push(format!("open {}", n));
+		}
+		if item.can_turn_on() && !item.is_on() {
+			commands.push(format!("turn on {}", n));
+		}
+		if item.has_under && !item.under_items.is_empty() {
+			commands.push(format!("look under {}", n));
+		}
+		if item.has_behind && !item.behind_items.is_empty() {
+			commands.push(format!("look behind {}", n));
+		}
+		if item.damaged_by.is_some() && item.damage_level() != DamageLevel::DESTROYED {
+			for tool in carried.iter().filter(|t| t.damage_type.is_some() && t.damage_type == item.damaged_by) {
+				let verb = match tool.damage_type {
+					Some(DamageType::CUTTING) => "cut",
+					_ => "hit",
+				};
+				commands.push(format!("{} {} with {}", verb, n, noun(tool)));
+			}
+		}
+		if item.can_attach {
+			for sub in carried.iter().filter(|s| s.can_attach && s.get_id() != item.get_id()) {
+				commands.push(format!("tie {} to {}", noun(sub), n));
+			}
+		}
+	}
+
+	let doors = location.doors();
+	let door_nouns: Vec<(&str, Vec<String>)> = doors.iter()
+		.map(|d| (d.i_type.as_str(), d.all_adjectives()))
+		.collect();
+	for door in &doors {
+		let n = name(&door.i_type, &door.all_adjectives(), &door_nouns);
+		if door.is_locked {
+			if let Some(key) = door.key.and_then(|k| carried.iter().find(|c| c.get_id() == k)) {
+				commands.push(format!("unlock {} with {}", n, noun(key)));
+			}
+		} else if !door.is_open {
+			commands.push(format!("open {}", n));
+		}
+	}
+	commands
+}
+
+/*
+Names a thing by its type, with an adjective in front if something else
+in view has the same type
+*/
+fn name(i_type: &str, adjectives: &[String], nouns: &[(&str, Vec<String>)]) -> String {
+	let others: Vec<&Vec<String>> = nouns.iter()
+		.filter(|&&(t, ref a)| t == i_type && a.as_slice() != adjectives)
+		.map(|(_, a)| a)
+		.collect();
+	if others.is_empty() {
+		return i_type.to_string();
+	}
+	match adjectives.iter().find(|a| others.iter().all(|o| !o.contains(a))) {
+		Some(a) => format!("{} {}", a, i_type),
+		None => i_type.to_string(),
+	}
+}
+
+impl fmt::Display for Report {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.solution {
+			Some(ref commands) => {
+				writeln!(f, "The world can be won in {} command{}:", commands.len(), if commands.len() == 1 { "" } else { "s" })?;
+				for command in commands {
+					writeln!(f, "\t{}", command)?;
+				}
+			},
+			None if self.complete => writeln!(f, "The world can't be won.")?,
+			None => writeln!(f, "No way to win was found.")?,
+		}
+		if !self.dead_ends.is_empty() {
+			writeln!(f, "These lead to dead ends, where the game can no longer be won:")?;
+			for commands in &self.dead_ends {
+				writeln!(f, "\t{}", commands.join(", "))?;
+			}
+		}
+		if self.complete {
+			write!(f, "Explored all {} positions.", self.states)
+		} else {
+			write!(f, "Stopped after exploring {} positions.", self.states)
+		}
+	}
+}
@@ -0,0 +1,112 @@
+/*
+Plays a world from a walkthrough script, checking the output as it goes.
+
+A walkthrough is a text file, committed beside the world it plays
+(temple.walkthrough for temple.world). Lines starting with # are
+comments. A line starting with > is a command, fed to the Engine as if
+typed. Every other line is a check on the output of the last command (or
+of the opening description, before the first command):
+
+	> e
+	room Entrance
+	contains you duck in the temple's entrance
+	> take idol
+	contains *** You have won ***
+	status won
+
+Checks:
+	room NAME: the player is in the location called NAME
+	contains TEXT: the output contains TEXT
+	lacks TEXT: the output doesn't contain TEXT
+	status playing|won|lost|ended|quit: the game has that status
+	ending NAME: the game finished with the ending called NAME
+
+Games saved by a walkthrough go in the system's temp directory.
+*/
+
+use std::env;
+use std::fs;
+
+use game::engine::{Engine,GameStatus,Output};
+use game::gamestate::GameState;
+use world::World;
+
+/*
+Runs the walkthrough file at path against the world
+*/
+pub fn run_walkthrough_file(path: &str, world: World) -> Result<(), String> {
+	match fs::read_to_string(path) {
+		Ok(script) => match run_walkthrough(&script, world) {
+			Ok(_) => Ok(()),
+			Err(e) => Err(format!("{}: {}", path, e)),
+		},
+		Err(e) => Err(format!("Could not read {}: {}", path, e)),
+	}
+}
+
+/*
+Plays the world from the start, following the script. Returns the
+first check that fails.
+*/
+pub fn run_walkthrough(script: &str, world: World) -> Result<(), String> {
+	let mut engine = Engine::new(GameState::from_world(world));
+	engine.set_save_dir(&env::temp_dir());
+	let mut command = "(start)".to_string();
+	let mut output = join_output(&engine.start());
+
+	for (i, line) in script.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let fail = |msg: String| Err(format!("line {}, after '{}': {}", i + 1, command, msg));
+
+		if let Some(input) = line.strip_prefix('>') {
+			command = input.trim().to_string();
+			output = join_output(&engine.step(&command));
+			continue;
+		}
+
+		let (check, arg) = match line.find(' ') {
+			Some(n) => (&line[..n], line[n + 1..].trim()),
+			None => (line, ""),
+		};
+		match check {
+			"room" => {
+				let name = &engine.game_state().current_location().name;
+				if name != arg {
+					return fail(format!("expected to be in '{}', but in '{}'", arg, name));
+				}
+			},
+			"contains" => if !output.contains(arg) {
+				return fail(format!("expected the output to contain '{}', got:\n{}", arg, output));
+			},
+			"lacks" => if output.contains(arg) {
+				return fail(format!("expected the output not to contain '{}', got:\n{}", arg, output));
+			},
+			"ending" => match engine.ending() {
+				Some(e) if e.name == arg => (),
+				Some(e) => return fail(format!("expected the ending '{}', got '{}'", arg, e.name)),
+				None => return fail(format!("expected the ending '{}', but the game hasn't ended", arg)),
+			},
+			"status" => {
+				let status = match engine.status() {
+					GameStatus::PLAYING => "playing",
+					GameStatus::WON => "won",
+					GameStatus::LOST => "lost",
+					GameStatus::ENDED => "ended",
+					GameStatus::QUIT => "quit",
+				};
+				if status != arg {
+					return fail(format!("expected the game to be {}, but it is {}", arg, status));
+				}
+			},
+			_ => return Err(format!("line {}: unknown check '{}'", i + 1, check)),
+		}
+	}
+	Ok(())
+}
+
+fn join_output(output: &Output) -> String {
+	output.text.join("\n")
+}
@@ -0,0 +1,26 @@
+/*
+RustADE, a text adventure engine.
+
+The library holds everything needed to build and play a world:
+	game: the Engine, GameState and the commands that act on them
+	obj: items, locations and the player
+	parsing: turning a line of input into a CommandNode
+	world: loading worlds from files, and the built in temple
+The rustade binary is a small stdin/stdout client of the Engine.
+*/
+
+#[macro_use]
+extern crate lazy_static;
+extern crate regex;
+extern crate uuid;
+
+pub mod game;
+pub mod obj;
+pub mod parsing;
+pub mod world;
+
+pub use game::engine::{Engine,GameStatus,Output};
+pub use game::gamestate::GameState;
+pub use obj::item::{Item,ItemBuilder};
+pub use obj::location::{Location,Exit,ExitBuilder};
+pub use obj::player::Player;
@@ -1,30 +1,145 @@
-#[macro_use]
-extern crate lazy_static;
-extern crate regex;
-extern crate uuid;
-
-mod game;
-mod obj;
-mod parsing;
-mod world;
-
-use game::gamestate::GameState;
-use game::gamecontroller;
-// use parsing::grammar::CommandNode;
-// use parsing::token::GameStateType;
-use world::builder;
+extern crate rustade;
+
+use std::env;
+use std::io;
+use std::process;
+use std::str::FromStr;
+use std::time::{SystemTime,UNIX_EPOCH};
+
+use rustade::{Engine,GameStatus,GameState,Output};
+use rustade::game::rng::Rng;
+use rustade::game::solver;
+use rustade::world::{World,builder,generator,loader,validator};
 
 fn main() {
-	let game_state = GameState::new(0, builder::build_fixed_world());
-
-	// println!("Completed on a{} command.", match gamecontroller::take_control(game_state) {
-	// 	Some(c) => match c {
-	// 		CommandNode::GAMESTATE(s) => match s {
-	// 			GameStateType::QUIT => " QUIT".to_string(),
-	// 		},
-	// 		_ => " valid".to_string(),
-	// 	},
-	// 	None => "n invalid".to_string(),
-	// })
-	gamecontroller::take_control(game_state);
-}
\ No newline at end of file
+	// An optional world file can be given, otherwise the temple is used.
+	// --undo N sets how many commands can be undone.
+	// --seed N seeds the game's random numbers, which otherwise come
+	// from the clock.
+	// --generate SEED plays a temple generated with that seed instead,
+	// and --depth, --branching and --puzzles say how big it is.
+	// "check" checks the world for mistakes instead of playing it, and
+	// "solve" looks for the shortest way to win it.
+	let mut world_path: Option<String> = None;
+	let mut undo_depth: Option<usize> = None;
+	let mut seed: Option<u64> = None;
+	let mut generate: Option<u64> = None;
+	let mut settings = generator::Settings::default();
+	let mut args = env::args().skip(1).peekable();
+	let tool = match args.peek().map(|a| a.as_str()) {
+		Some("check") | Some("solve") => args.next(),
+		_ => None,
+	};
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"--undo" => undo_depth = Some(number(args.next(), "--undo needs a number of commands")),
+			"--seed" => seed = Some(number(args.next(), "--seed needs a number")),
+			"--generate" => generate = Some(number(args.next(), "--generate needs a seed")),
+			"--depth" => settings.depth = number(args.next(), "--depth needs a number of rooms"),
+			"--branching" => settings.branching = number(args.next(), "--branching needs a number of branches"),
+			"--puzzles" => settings.puzzles = number(args.next(), "--puzzles needs a number of puzzles"),
+			_ => world_path = Some(arg),
+		}
+	}
+
+	let mut rng = match (generate, seed) {
+		(Some(g), Some(s)) if g != s => {
+			eprintln!("--generate and --seed give different seeds");
+			process::exit(1);
+		},
+		(Some(s), _) | (None, Some(s)) => Rng::new(s),
+		(None, None) => Rng::new(clock_seed()),
+	};
+	let world = match (generate, world_path) {
+		(Some(_), _) => generator::generate_from(&mut rng, &settings),
+		(None, Some(path)) => match loader::load_world_file(&path) {
+			Ok(l) => l,
+			Err(e) => {
+				eprintln!("{}", e);
+				process::exit(1);
+			},
+		},
+		(None, None) => builder::build_fixed_world(),
+	};
+	match tool.as_deref() {
+		Some("check") => check_world(&world),
+		Some("solve") => solve_world(world.clone()),
+		_ => (),
+	}
+	let mut game_state = GameState::from_world(world);
+	game_state.set_rng(rng);
+	if let Some(depth) = undo_depth {
+		game_state.set_undo_depth(depth);
+	}
+
+
+	let mut engine = Engine::new(game_state);
+	print_output(&engine.start());
+	loop {
+		let mut input = String::new();
+		match io::stdin().read_line(&mut input) {
+			Ok(0) | Err(_) => break,
+			Ok(_) => (),
+		}
+		print_output(&engine.step(&input));
+		if engine.status() != GameStatus::PLAYING && !engine.is_asking() {
+			break;
+		}
+	}
+}
+
+/*
+Parses the value after a flag, or exits with message if there isn't a
+number there
+*/
+fn number<T: FromStr>(arg: Option<String>, message: &str) -> T {
+	match arg.map(|n| n.parse::<T>()) {
+		Some(Ok(n)) => n,
+		_ => {
+			eprintln!("{}", message);
+			process::exit(1);
+		},
+	}
+}
+
+/*
+Makes a seed from the time, for games started without one
+*/
+fn clock_seed() -> u64 {
+	match SystemTime::now().duration_since(UNIX_EPOCH) {
+		Ok(d) => d.as_secs() ^ (u64::from(d.subsec_nanos()) << 32),
+		Err(_) => 0,
+	}
+}
+
+/*
+Prints every problem with the world and exits, with status 1 if any of
+them is an error
+*/
+fn check_world(world: &World) {
+	let problems = world.validate();
+	if problems.is_empty() {
+		println!("No problems found.");
+	}
+	for problem in &problems {
+		println!("{}", problem);
+	}
+	process::exit(if validator::has_errors(&problems) { 1 } else { 0 });
+}
+
+/*
+Prints the shortest way to win the world, and any dead ends, and exits,
+with status 1 if it can't be won
+*/
+fn solve_world(world: World) {
+	let report = solver::solve(world, solver::DEFAULT_MAX_STATES);
+	println!("{}", report);
+	process::exit(if report.solution.is_some() { 0 } else { 1 });
+}
+
+fn print_output(output: &Output) {
+	for block in &output.text {
+		println!("\n{}", block);
+	}
+	println!();
+}
@@ -0,0 +1,157 @@
+/*
+Doors, which sit in an exit and can be opened, closed, locked and
+unlocked.
+
+A door belongs to both sides of a passage. Each of the two exits holds a
+copy with the same id, and GameState changes every copy at once, so the
+door is always in the same state from either room. A closed door blocks
+its exit. A door with a key can be locked and unlocked by a player
+carrying that key.
+*/
+
+use std::fmt;
+use uuid::Uuid;
+
+use game::save::{SaveWriter,SaveReader};
+use obj::item::name_adjectives;
+use parsing::grammar::ItemNode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Door {
+	id: Uuid,
+	pub i_type: String,
+	pub name: String,
+	pub aliases: Vec<String>,
+	pub adjectives: Vec<String>,
+	pub desc: String,
+	pub is_open: bool,
+	pub is_locked: bool,
+	pub key: Option<Uuid>, // None means it has no lock
+}
+
+impl Door {
+	/*
+	Creates a closed, unlocked Door without a lock.
+	*/
+	pub fn new(i_type: &str, name: &str, desc: &str) -> Door {
+		Door {
+			id: Uuid::new_v4(),
+			i_type: i_type.to_string(),
+			name: name.to_string(),
+			aliases: Vec::new(),
+			adjectives: Vec::new(),
+			desc: desc.to_string(),
+			is_open: false,
+			is_locked: false,
+			key: None,
+		}
+	}
+
+	pub fn get_id(&self) -> Uuid {
+		self.id
+	}
+
+	/*
+	Gives the door a known id, instead of the random one it was created
+	with
+	*/
+	pub fn set_id(&mut self, id: Uuid) {
+		self.id = id;
+	}
+
+	/*
+	Returns true if the door is the one the player referred to, the same
+	way as Item::matches
+	*/
+	pub fn matches(&self, i_node: &ItemNode) -> bool {
+		if self.i_type != i_node.subject {
+			return false;
+		}
+		let adjectives = self.all_adjectives();
+		i_node.adjectives.iter().all(|a| adjectives.contains(a))
+	}
+
+	pub fn all_adjectives(&self) -> Vec<String> {
+		name_adjectives(&self.name, &self.i_type, &self.aliases, &self.adjectives)
+	}
+
+	/*
+	Describes whether the door is open, closed or locked
+	*/
+	pub fn state_desc(&self) -> String {
+		format!("The {} is {}.", self.i_type, if self.is_open {
+			"open"
+		} else if self.is_locked {
+			"locked"
+		} else {
+			"closed"
+		})
+	}
+
+	/*
+	Writes the door to a save file
+	*/
+	pub fn save(&self, w: &mut SaveWriter) {
+		w.begin("door");
+		w.value("id", self.id);
+		w.string("i_type", &self.i_type);
+		w.string("name", &self.name);
+		for alias in &self.aliases {
+			w.string("alias", alias);
+		}
+		for adjective in &self.adjectives {
+			w.string("adjective", adjective);
+		}
+		w.string("desc", &self.desc);
+		w.value("is_open", self.is_open);
+		w.value("is_locked", self.is_locked);
+		if let Some(key) = self.key {
+			w.value("key", key);
+		}
+		w.end();
+	}
+
+	/*
+	Reads a door written by save
+	*/
+	pub fn load(r: &mut SaveReader) -> Result<Door, String> {
+		r.begin("door")?;
+		let id = r.parse("id")?;
+		let i_type = r.string("i_type")?;
+		let name = r.string("name")?;
+		let mut aliases = Vec::new();
+		while r.peek_key() == Some("alias") {
+			aliases.push(r.string("alias")?);
+		}
+		let mut adjectives = Vec::new();
+		while r.peek_key() == Some("adjective") {
+			adjectives.push(r.string("adjective")?);
+		}
+		let door = Door {
+			id,
+			i_type,
+			name,
+			aliases,
+			adjectives,
+			desc: r.string("desc")?,
+			is_open: r.parse("is_open")?,
+			is_locked: r.parse("is_locked")?,
+			key: match r.peek_key() {
+				Some("key") => Some(r.parse("key")?),
+				_ => None,
+			},
+		};
+		r.end()?;
+		Ok(door)
+	}
+}
+
+impl fmt::Display for Door {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.desc.is_empty() {
+			write!(f, "{}", self.state_desc())
+		} else {
+			write!(f, "{} {}", self.desc, self.state_desc())
+		}
+	}
+}
@@ -15,13 +15,31 @@ Version 0.1.4
 use std::fmt;
 use uuid::Uuid;
 
+use game::save::{SaveWriter,SaveReader};
 use obj::{DamageType,DamageLevel};
+use obj::template::{self,Scope};
+use parsing::PrepositionType;
+use parsing::grammar::ItemNode;
 
-#[derive(Clone, PartialEq)]
+// Fuel left when a light source warns that it is running low
+const DIM_FUEL: i32 = 10;
+const FLICKER_FUEL: i32 = 3;
+
+// Fuel is told apart in steps of this many turns when comparing items
+const FUEL_STEP: i32 = 10;
+
+/*
+Words in item names that are never used as adjectives
+*/
+const IGNORED_NAME_WORDS: [&str; 6] = ["a", "an", "the", "some", "of", "and"];
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Item {
 	id: Uuid,
 	pub i_type: String,
 	pub name: String,
+	pub aliases: Vec<String>,
+	pub adjectives: Vec<String>,
 	// Descriptions
 	pub desc: String,
 	pub is_on_desc: String,
@@ -35,8 +53,16 @@ pub struct Item {
 	pub has_inside: bool,
 	pub has_behind: bool,
 	pub has_under: bool,
+	pub can_open: bool,
+	pub is_open: bool,
+	pub can_attach: bool,
 	can_turn_on: bool,
 	is_on: bool,
+	gives_light: bool,
+	fuel: i32, // Turns of fuel left while on, -1 means it never runs out
+	// Points scored the first time it is taken or destroyed
+	pub take_points: i32,
+	pub destroy_points: i32,
 	// Health and Damage
 	pub max_health: i32,
 	pub current_health: i32,
@@ -48,6 +74,26 @@ pub struct Item {
 	pub in_items: Vec<Item>,
 	pub behind_items: Vec<Item>,
 	pub under_items: Vec<Item>,
+	pub attached_items: Vec<Item>,
+}
+
+/*
+The parts of an item that can change as the game is played, and the
+same for everything it holds. Two items with the same key are in the
+same state, so far as playing on is concerned.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ItemKey {
+	id: Uuid,
+	is_open: bool,
+	is_on: bool,
+	fuel: i32, // In steps of FUEL_STEP, rounded up so that only an empty light is 0
+	health: i32,
+	on_items: Vec<ItemKey>,
+	in_items: Vec<ItemKey>,
+	behind_items: Vec<ItemKey>,
+	under_items: Vec<ItemKey>,
+	attached_items: Vec<ItemKey>,
 }
 
 impl Item {
@@ -56,16 +102,88 @@ impl Item {
 		self.id
 	}
 
+	/*
+	Returns true if the item is the one the player referred to: the type
+	must match, and every adjective used must belong to the item.
+	*/
+	pub fn matches(&self, i_node: &ItemNode) -> bool {
+		if self.i_type != i_node.subject {
+			return false;
+		}
+		let adjectives = self.all_adjectives();
+		i_node.adjectives.iter().all(|a| adjectives.contains(a))
+	}
+
+	/*
+	Returns the item's adjectives along with the words of its name, other
+	than articles and the nouns the item already goes by.
+	*/
+	pub fn all_adjectives(&self) -> Vec<String> {
+		name_adjectives(&self.name, &self.i_type, &self.aliases, &self.adjectives)
+	}
+
 	pub fn is_blocking(&self) -> bool {
-		(self.max_health > 0 && self.current_health > 0) || (self.can_attach && self.attached_items.len() == 0) || self.is_on
+		(self.max_health > 0 && self.current_health > 0) || (self.can_attach && self.attached_items.is_empty()) || self.is_on
 	}
 
-	pub fn toggle_on(&mut self) -> Option<bool> {
-		if self.can_turn_on {
-			self.is_on = !self.is_on;
-			Some(self.is_on)
-		} else {
+	/*
+	Turns the item on or off. Returns None if the item can't be turned
+	on, otherwise whether it is now in the state asked for (an item out
+	of fuel won't turn on).
+	*/
+	pub fn set_on(&mut self, b: bool) -> Option<bool> {
+		if !self.can_turn_on {
 			None
+		} else if b && self.fuel == 0 {
+			Some(false)
+		} else {
+			self.is_on = b;
+			Some(true)
+		}
+	}
+
+	pub fn is_on(&self) -> bool {
+		self.is_on
+	}
+
+	pub fn can_turn_on(&self) -> bool {
+		self.can_turn_on
+	}
+
+	/*
+	Returns true if the item, or anything visible on, in or attached to
+	it, is lighting the area
+	*/
+	pub fn has_light(&self) -> bool {
+		(self.gives_light && self.is_on) || self.visible_items().iter().any(|i| i.has_light())
+	}
+
+	/*
+	Burns a turn of fuel from this item and everything it holds, turning
+	off anything that runs out. Warnings are only written to display if
+	report is true.
+	*/
+	pub fn burn_fuel(&mut self, display: &mut String, report: bool) {
+		if self.is_on && self.fuel > 0 {
+			self.fuel -= 1;
+			if report {
+				match self.fuel {
+					0 => display.push_str(format!("The {} flickers and goes out.\n", self.i_type).as_str()),
+					FLICKER_FUEL => display.push_str(format!("The {} is flickering.\n", self.i_type).as_str()),
+					DIM_FUEL => display.push_str(format!("The {} is growing dim.\n", self.i_type).as_str()),
+					_ => (),
+				}
+			}
+			if self.fuel == 0 {
+				self.is_on = false;
+			}
+		}
+		for item in self.on_items.iter_mut()
+			.chain(self.in_items.iter_mut())
+			.chain(self.behind_items.iter_mut())
+			.chain(self.under_items.iter_mut())
+			.chain(self.attached_items.iter_mut()) {
+			item.burn_fuel(display, report);
 		}
 	}
 
@@ -73,31 +191,43 @@ impl Item {
 		self.to_dmg += amount;
 	}
 
+	/*
+	Destroys the item outright, even one that can't be damaged
+	*/
+	pub fn destroy(&mut self) {
+		if self.max_health <= 0 {
+			self.max_health = 1;
+		}
+		self.current_health = 0;
+		self.to_dmg = 0;
+	}
+
 // println!("HP for {}:\nmax: {}\ncurrent: {}\ndamage: {}\n", self.name, self.max_health, self.current_health, self.to_dmg);
 
-	pub fn update(&mut self, mut display: &mut String) {
-		if !self.is_scenery {
-			let dmg_lvl = self.damage_level();
-			self.current_health -= self.to_dmg;
-			if self.current_health < 0 {
-				self.current_health = 0;
-			}
-			if dmg_lvl != self.damage_level() {
-				display.push_str(format!("{}\n", self).as_str());
-			}
-			for item in &mut self.on_items {
-				item.update(&mut display);
-			}
-			for item in &mut self.in_items {
-				item.update(&mut display);
-			}
-			for item in &mut self.behind_items {
-				item.update(&mut display);
-			}
-			for item in &mut self.under_items {
-				item.update(&mut display);
-			}
-			self.to_dmg = 0;
+	pub fn update(&mut self, display: &mut String) {
+		let dmg_lvl = self.damage_level();
+		self.current_health -= self.to_dmg;
+		if self.current_health < 0 {
+			self.current_health = 0;
+		}
+		if dmg_lvl != self.damage_level() {
+			display.push_str(format!("{}\n", self).as_str());
+		}
+		self.to_dmg = 0;
+		for item in &mut self.on_items {
+			item.update(display);
+		}
+		for item in &mut self.in_items {
+			item.update(display);
+		}
+		for item in &mut self.behind_items {
+			item.update(display);
+		}
+		for item in &mut self.under_items {
+			item.update(display);
+		}
+		for item in &mut self.attached_items {
+			item.update(display);
 		}
 	}
 
@@ -115,8 +245,165 @@ impl Item {
 		result
 	}
 
-	pub fn put_item_on_this(&mut self, item: Item) {
-		self.on_items.push(item);
+	/*
+	Returns true if the items inside this item can be seen and reached,
+	meaning it is either open or can't be closed.
+	*/
+	pub fn is_inside_visible(&self) -> bool {
+		!self.can_open || self.is_open
+	}
+
+	/*
+	Opens or closes the item. Returns None if the item can't be opened,
+	otherwise whether the open state changed.
+	*/
+	pub fn set_open(&mut self, b: bool) -> Option<bool> {
+		if self.can_open {
+			let changed = self.is_open != b;
+			self.is_open = b;
+			Some(changed)
+		} else {
+			None
+		}
+	}
+
+	/*
+	Returns true if an item could be put in/on/under/behind this item
+	right now.
+	*/
+	pub fn can_hold(&self, p: PrepositionType) -> bool {
+		match p {
+			PrepositionType::ON => self.has_surface,
+			PrepositionType::IN => self.has_inside && self.is_inside_visible(),
+			PrepositionType::UNDER => self.has_under,
+			PrepositionType::BEHIND => self.has_behind,
+			_ => false,
+		}
+	}
+
+	/*
+	Puts an item in/on/under/behind this item. If it can't hold the item
+	there, the item is handed back, otherwise None is returned.
+	*/
+	pub fn put_item(&mut self, p: PrepositionType, item: Item) -> Option<Item> {
+		if !self.can_hold(p) {
+			return Some(item);
+		}
+		self.place_item(p, item)
+	}
+
+	/*
+	Same as put_item, but ignores whether the item is open. Used when
+	building worlds, so closed containers can start with things inside.
+	*/
+	pub fn place_item(&mut self, p: PrepositionType, item: Item) -> Option<Item> {
+		match p {
+			PrepositionType::ON if self.has_surface => self.on_items.push(item),
+			PrepositionType::IN if self.has_inside => self.in_items.push(item),
+			PrepositionType::UNDER if self.has_under => self.under_items.push(item),
+			PrepositionType::BEHIND if self.has_behind => self.behind_items.push(item),
+			_ => return Some(item),
+		}
+		None
+	}
+
+	/*
+	Removes and returns the items hidden under or behind this item, so
+	they can be brought into view.
+	*/
+	pub fn take_hidden_items(&mut self, p: PrepositionType) -> Vec<Item> {
+		match p {
+			PrepositionType::UNDER => self.under_items.drain(..).collect(),
+			PrepositionType::BEHIND => self.behind_items.drain(..).collect(),
+			_ => Vec::new(),
+		}
+	}
+
+	pub fn attach_item(&mut self, item: Item) {
+		self.attached_items.push(item);
+	}
+
+	/*
+	Returns this item and every item it holds, hidden or not
+	*/
+	pub fn all_items(&self) -> Vec<&Item> {
+		let mut items = vec![self];
+		for item in self.on_items.iter()
+			.chain(self.in_items.iter())
+			.chain(self.behind_items.iter())
+			.chain(self.under_items.iter())
+			.chain(self.attached_items.iter()) {
+			items.extend(item.all_items());
+		}
+		items
+	}
+
+	/*
+	Returns the items that can be seen on, in or attached to this item.
+	Items behind or under it stay hidden.
+	*/
+	pub fn visible_items(&self) -> Vec<&Item> {
+		let mut items: Vec<&Item> = self.on_items.iter().collect();
+		if self.is_inside_visible() {
+			items.extend(self.in_items.iter());
+		}
+		items.extend(self.attached_items.iter());
+		items
+	}
+
+	/*
+	Searches the visible items of this item (and their visible items) for
+	one matching i_node.
+	*/
+	pub fn find_item(&self, i_node: &ItemNode) -> Option<Item> {
+		for item in self.visible_items() {
+			if item.matches(i_node) {
+				return Some(item.clone());
+			}
+			if let Some(i) = item.find_item(i_node) {
+				return Some(i);
+			}
+		}
+		None
+	}
+
+	/*
+	Same as find_item, but returns a mutable reference to the item.
+	*/
+	pub fn find_item_mut(&mut self, i_node: &ItemNode) -> Option<&mut Item> {
+		let inside_visible = self.is_inside_visible();
+		let mut items: Vec<&mut Item> = self.on_items.iter_mut().collect();
+		if inside_visible {
+			items.extend(self.in_items.iter_mut());
+		}
+		items.extend(self.attached_items.iter_mut());
+		for item in items {
+			if item.matches(i_node) {
+				return Some(item);
+			}
+			if let Some(i) = item.find_item_mut(i_node) {
+				return Some(i);
+			}
+		}
+		None
+	}
+
+	/*
+	Removes the first visible item matching i_node from this item or
+	anything it holds.
+	*/
+	pub fn remove_item(&mut self, i_node: &ItemNode) -> Option<Item> {
+		match remove_item_from(&mut self.on_items, i_node) {
+			Some(i) => Some(i),
+			None => {
+				if self.is_inside_visible() {
+					if let Some(i) = remove_item_from(&mut self.in_items, i_node) {
+						return Some(i);
+					}
+				}
+				remove_item_from(&mut self.attached_items, i_node)
+			},
+		}
 	}
 
 	pub fn find_item_by_id(&self, id: Uuid) -> Option<Item> {
@@ -124,42 +411,309 @@ impl Item {
 			Some(self.clone())
 		} else {
 			let mut found_item: Option<Item> = None;
-			for item in &self.attached_items {
-				match item.find_item_by_id(id) {
-					Some(i) => {
-						found_item = Some(i);
-						break;
-					},
-					None => (),
+			for item in self.on_items.iter()
+				.chain(self.in_items.iter())
+				.chain(self.behind_items.iter())
+				.chain(self.under_items.iter())
+				.chain(self.attached_items.iter()) {
+				if let Some(i) = item.find_item_by_id(id) {
+					found_item = Some(i);
+					break;
 				}
 			};
 			found_item
 		}
 	}
 
-	pub fn display_item_in_inventory(&self, mut display: &mut String) {
-		display.push_str(format!("")
+	/*
+	Same as find_item_by_id, but returns a mutable reference to the item.
+	*/
+	pub fn find_item_by_id_mut(&mut self, id: Uuid) -> Option<&mut Item> {
+		if self.id == id {
+			return Some(self);
+		}
+		self.on_items.iter_mut()
+			.chain(self.in_items.iter_mut())
+			.chain(self.behind_items.iter_mut())
+			.chain(self.under_items.iter_mut())
+			.chain(self.attached_items.iter_mut())
+			.filter_map(|i| i.find_item_by_id_mut(id))
+			.next()
+	}
+
+	/*
+	Describes the visible items on, in and attached to this item, one
+	sentence per line.
+	*/
+	pub fn display_contents(&self) -> String {
+		let mut display = String::new();
+		if !self.on_items.is_empty() {
+			display.push_str(format!("\nOn the {} is {}.", self.i_type, list_item_names(&self.on_items)).as_str());
+		}
+		if self.is_inside_visible() && !self.in_items.is_empty() {
+			display.push_str(format!("\nIn the {} is {}.", self.i_type, list_item_names(&self.in_items)).as_str());
+		}
+		if !self.attached_items.is_empty() {
+			display.push_str(format!("\nAttached to the {} is {}.", self.i_type, list_item_names(&self.attached_items)).as_str());
+		}
+		for item in self.visible_items() {
+			display.push_str(&item.display_contents());
+		}
+		display
+	}
+
+	/*
+	Returns the key of the item and everything it holds, used by
+	GameState::state_key
+	*/
+	pub fn key(&self) -> ItemKey {
+		let keys = |items: &[Item]| items.iter().map(|i| i.key()).collect();
+		ItemKey {
+			id: self.id,
+			is_open: self.is_open,
+			is_on: self.is_on,
+			fuel: if self.fuel > 0 { (self.fuel + FUEL_STEP - 1) / FUEL_STEP } else { self.fuel },
+			health: self.current_health,
+			on_items: keys(&self.on_items),
+			in_items: keys(&self.in_items),
+			behind_items: keys(&self.behind_items),
+			under_items: keys(&self.under_items),
+			attached_items: keys(&self.attached_items),
+		}
+	}
+
+	/*
+	Writes the item, and everything it holds, to a save file
+	*/
+	pub fn save(&self, w: &mut SaveWriter) {
+		w.begin("item");
+		w.value("id", self.id);
+		w.string("i_type", &self.i_type);
+		w.string("name", &self.name);
+		for alias in &self.aliases {
+			w.string("alias", alias);
+		}
+		for adjective in &self.adjectives {
+			w.string("adjective", adjective);
+		}
+		w.string("desc", &self.desc);
+		w.string("is_on_desc", &self.is_on_desc);
+		w.string("light_damage_desc", &self.light_damage_desc);
+		w.string("heavy_damage_desc", &self.heavy_damage_desc);
+		w.string("destroyed_desc", &self.destroyed_desc);
+		w.value("is_fixed", self.is_fixed);
+		w.value("is_scenery", self.is_scenery);
+		w.value("has_surface", self.has_surface);
+		w.value("has_inside", self.has_inside);
+		w.value("has_behind", self.has_behind);
+		w.value("has_under", self.has_under);
+		w.value("can_open", self.can_open);
+		w.value("is_open", self.is_open);
+		w.value("can_attach", self.can_attach);
+		w.value("can_turn_on", self.can_turn_on);
+		w.value("is_on", self.is_on);
+		w.value("gives_light", self.gives_light);
+		w.value("fuel", self.fuel);
+		w.value("take_points", self.take_points);
+		w.value("destroy_points", self.destroy_points);
+		w.value("max_health", self.max_health);
+		w.value("current_health", self.current_health);
+		w.value("to_dmg", self.to_dmg);
+		w.damage_type("damaged_by", &self.damaged_by);
+		w.damage_type("damage_type", &self.damage_type);
+		save_items(w, "on_items", &self.on_items);
+		save_items(w, "in_items", &self.in_items);
+		save_items(w, "behind_items", &self.behind_items);
+		save_items(w, "under_items", &self.under_items);
+		save_items(w, "attached_items", &self.attached_items);
+		w.end();
+	}
+
+	/*
+	Reads an item written by save
+	*/
+	pub fn load(r: &mut SaveReader) -> Result<Item, String> {
+		r.begin("item")?;
+		let id = r.parse("id")?;
+		let i_type = r.string("i_type")?;
+		let name = r.string("name")?;
+		let mut aliases = Vec::new();
+		while r.peek_key() == Some("alias") {
+			aliases.push(r.string("alias")?);
+		}
+		let mut adjectives = Vec::new();
+		while r.peek_key() == Some("adjective") {
+			adjectives.push(r.string("adjective")?);
+		}
+		let item = Item {
+			id,
+			i_type,
+			name,
+			aliases,
+			adjectives,
+			desc: r.string("desc")?,
+			is_on_desc: r.string("is_on_desc")?,
+			light_damage_desc: r.string("light_damage_desc")?,
+			heavy_damage_desc: r.string("heavy_damage_desc")?,
+			destroyed_desc: r.string("destroyed_desc")?,
+			is_fixed: r.parse("is_fixed")?,
+			is_scenery: r.parse("is_scenery")?,
+			has_surface: r.parse("has_surface")?,
+			has_inside: r.parse("has_inside")?,
+			has_behind: r.parse("has_behind")?,
+			has_under: r.parse("has_under")?,
+			can_open: r.parse("can_open")?,
+			is_open: r.parse("is_open")?,
+			can_attach: r.parse("can_attach")?,
+			can_turn_on: r.parse("can_turn_on")?,
+			is_on: r.parse("is_on")?,
+			gives_light: r.parse("gives_light")?,
+			fuel: r.parse("fuel")?,
+			take_points: r.parse("take_points")?,
+			destroy_points: r.parse("destroy_points")?,
+			max_health: r.parse("max_health")?,
+			current_health: r.parse("current_health")?,
+			to_dmg: r.parse("to_dmg")?,
+			damaged_by: r.damage_type("damaged_by")?,
+			damage_type: r.damage_type("damage_type")?,
+			on_items: load_items(r, "on_items")?,
+			in_items: load_items(r, "in_items")?,
+			behind_items: load_items(r, "behind_items")?,
+			under_items: load_items(r, "under_items")?,
+			attached_items: load_items(r, "attached_items")?,
+		};
+		r.end()?;
+		Ok(item)
+	}
+
+	pub fn display_item_in_inventory(&self, display: &mut String) {
+		self.display_item_at_depth(display, 1);
+	}
+
+	fn display_item_at_depth(&self, display: &mut String, depth: usize) {
+		display.push('\n');
+		for _ in 0..depth {
+			display.push('\t');
+		}
+		display.push_str(&self.name);
+		if self.can_open && !self.is_open {
+			display.push_str(" (closed)");
+		}
+		for item in self.visible_items() {
+			item.display_item_at_depth(display, depth + 1);
+		}
+	}
+}
+
+/*
+Returns the adjectives along with the words of the name, other than
+articles and the nouns (type and aliases) it already goes by. Shared by
+items and doors.
+*/
+pub fn name_adjectives(name: &str, i_type: &str, aliases: &[String], adjectives: &[String]) -> Vec<String> {
+	let mut all: Vec<String> = adjectives.iter().map(|a| a.to_lowercase()).collect();
+	for word in name.split_whitespace().map(|w| w.to_lowercase()) {
+		if word != i_type.to_lowercase()
+			&& !IGNORED_NAME_WORDS.contains(&word.as_str())
+			&& !aliases.iter().any(|a| a.to_lowercase() == word) {
+			all.push(word);
+		}
+	}
+	all
+}
+
+/*
+Writes a list of items to a save file as a block
+*/
+pub fn save_items(w: &mut SaveWriter, block: &str, items: &[Item]) {
+	w.begin(block);
+	for item in items {
+		item.save(w);
+	}
+	w.end();
+}
+
+/*
+Reads a list of items written by save_items
+*/
+pub fn load_items(r: &mut SaveReader, block: &str) -> Result<Vec<Item>, String> {
+	r.begin(block)?;
+	let mut items = Vec::new();
+	while r.peek_key() == Some("begin") {
+		items.push(Item::load(r)?);
+	}
+	r.end()?;
+	Ok(items)
+}
+
+/*
+Removes the first item matching i_node from a list of items, searching
+the visible items of each item if it isn't found at the top.
+*/
+pub fn remove_item_from(items: &mut Vec<Item>, i_node: &ItemNode) -> Option<Item> {
+	match items.iter().position(|n| n.matches(i_node)) {
+		Some(i) => Some(items.remove(i)),
+		None => {
+			for item in items.iter_mut() {
+				if let Some(i) = item.remove_item(i_node) {
+					return Some(i);
+				}
+			}
+			None
+		},
+	}
+}
+
+/*
+Joins the names of a list of items into an English list:
+	a rope
+	a rope and a machete
+	a rope, a machete, and a pickaxe
+*/
+pub fn list_item_names(items: &[Item]) -> String {
+	let mut display = String::new();
+	let n = items.len();
+	for (i, item) in items.iter().enumerate() {
+		if i > 0 {
+			if n != 2 {
+				display.push(',');
+			}
+			display.push(' ');
+			if i == n - 1 {
+				display.push_str("and ");
+			}
+		}
+		display.push_str(&item.name);
 	}
+	display
 }
 
 impl fmt::Display for Item {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "{}{}", match self.damage_level() {
-			DamageLevel::NODAMAGE => self.desc.clone(),
-			DamageLevel::LIGHT => self.light_damage_desc.clone(),
-			DamageLevel::HEAVY => self.heavy_damage_desc.clone(),
-			DamageLevel::DESTROYED => self.destroyed_desc.clone(),
-		}, if self.is_on {
-			format!(" {}", self.is_on_desc.clone())
+		let scope = Scope {
+			this: Some(self),
+			..Scope::default()
+		};
+		write!(f, "{}{}", template::render(match self.damage_level() {
+			DamageLevel::NODAMAGE => &self.desc,
+			DamageLevel::LIGHT => &self.light_damage_desc,
+			DamageLevel::HEAVY => &self.heavy_damage_desc,
+			DamageLevel::DESTROYED => &self.destroyed_desc,
+		}, &scope), if self.is_on {
+			format!(" {}", template::render(&self.is_on_desc, &scope))
 		} else {
 			"".to_string()
 		})
 	}
 }
 
+#[derive(Debug)]
 pub struct ItemBuilder {
+	id: Option<Uuid>, // None gives a new random id
 	i_type: String,
 	name: String,
+	aliases: Vec<String>,
+	adjectives: Vec<String>,
 	// Descriptions
 	desc: String,
 	is_on_desc: String,
@@ -173,8 +727,15 @@ pub struct ItemBuilder {
 	has_inside: bool,
 	has_behind: bool,
 	has_under: bool,
+	can_open: bool,
+	is_open: bool,
+	can_attach: bool,
 	can_turn_on: bool,
 	is_on: bool,
+	gives_light: bool,
+	fuel: i32,
+	take_points: i32,
+	destroy_points: i32,
 	// Health and Damage
 	max_health: i32,
 	current_health: i32,
@@ -188,8 +749,11 @@ impl ItemBuilder {
 		match it {
 			"UNKNOWN" => panic!("Cannot create an item with type UNKNOWN."),
 			_ => ItemBuilder {
+				id: None,
 				i_type: it.to_string(),
 				name: n.to_string(),
+				aliases: Vec::new(),
+				adjectives: Vec::new(),
 				desc: d.to_string(),
 				is_on_desc: d.to_string(),
 				light_damage_desc: d.to_string(),
@@ -201,8 +765,15 @@ impl ItemBuilder {
 				has_inside: false,
 				has_behind: false,
 				has_under: false,
+				can_open: false,
+				is_open: false,
+				can_attach: false,
 				can_turn_on: false,
 				is_on: false,
+				gives_light: false,
+				fuel: -1,
+				take_points: 0,
+				destroy_points: 0,
 				max_health: -1,
 				current_health: -1,
 				damaged_by: None,
@@ -211,6 +782,33 @@ impl ItemBuilder {
 		}
 	}
 
+	/*
+	Gives the item a known id, instead of a new random one. Items that
+	have to be found again after a save is restored need one.
+	*/
+	pub fn set_id(&mut self, id: Uuid) -> &mut ItemBuilder {
+		self.id = Some(id);
+		self
+	}
+
+	/*
+	Adds another noun the player can use for the item, like "pick" for a
+	pickaxe.
+	*/
+	pub fn add_alias(&mut self, s: &str) -> &mut ItemBuilder {
+		self.aliases.push(s.to_string());
+		self
+	}
+
+	/*
+	Adds a word the player can put before the item's noun, like "bronze"
+	in "take bronze key".
+	*/
+	pub fn add_adjective(&mut self, s: &str) -> &mut ItemBuilder {
+		self.adjectives.push(s.to_string());
+		self
+	}
+
 	pub fn set_is_on_desc(&mut self, s: &str) -> &mut ItemBuilder {
 		self.is_on_desc = s.to_string();
 		self
@@ -261,6 +859,21 @@ impl ItemBuilder {
 		self
 	}
 
+	pub fn set_can_open(&mut self, b: bool) -> &mut ItemBuilder {
+		self.can_open = b;
+		self
+	}
+
+	pub fn set_is_open(&mut self, b: bool) -> &mut ItemBuilder {
+		self.is_open = b;
+		self
+	}
+
+	pub fn set_can_attach(&mut self, b: bool) -> &mut ItemBuilder {
+		self.can_attach = b;
+		self
+	}
+
 	pub fn set_can_turn_on(&mut self, b: bool) -> &mut ItemBuilder {
 		self.can_turn_on = b;
 		self
@@ -271,6 +884,38 @@ impl ItemBuilder {
 		self
 	}
 
+	/*
+	Makes the item light up dark locations while it is on.
+	*/
+	pub fn set_gives_light(&mut self, b: bool) -> &mut ItemBuilder {
+		self.gives_light = b;
+		self
+	}
+
+	/*
+	Sets how many turns the item can stay on before it runs out.
+	*/
+	pub fn set_fuel(&mut self, turns: i32) -> &mut ItemBuilder {
+		self.fuel = turns;
+		self
+	}
+
+	/*
+	Sets the points scored the first time the player picks the item up.
+	*/
+	pub fn set_take_points(&mut self, points: i32) -> &mut ItemBuilder {
+		self.take_points = points;
+		self
+	}
+
+	/*
+	Sets the points scored when the item is destroyed.
+	*/
+	pub fn set_destroy_points(&mut self, points: i32) -> &mut ItemBuilder {
+		self.destroy_points = points;
+		self
+	}
+
 	pub fn set_max_health(&mut self, hp: i32) -> &mut ItemBuilder {
 		self.max_health = hp;
 		self
@@ -299,9 +944,11 @@ impl ItemBuilder {
 
 	pub fn finalize(&self) -> Item {
 		Item {
-			id: Uuid::new_v4(),
+			id: self.id.unwrap_or_else(Uuid::new_v4),
 			i_type: self.i_type.clone(),
 			name: self.name.clone(),
+			aliases: self.aliases.clone(),
+			adjectives: self.adjectives.clone(),
 			desc: self.desc.clone(),
 			is_on_desc: self.is_on_desc.clone(),
 			light_damage_desc: self.light_damage_desc.clone(),
@@ -313,8 +960,15 @@ impl ItemBuilder {
 			has_inside: self.has_inside,
 			has_behind: self.has_behind,
 			has_under: self.has_under,
+			can_open: self.can_open,
+			is_open: self.is_open,
+			can_attach: self.can_attach,
 			can_turn_on: self.can_turn_on,
 			is_on: self.is_on,
+			gives_light: self.gives_light,
+			fuel: self.fuel,
+			take_points: self.take_points,
+			destroy_points: self.destroy_points,
 			max_health: self.max_health,
 			current_health: self.current_health,
 			to_dmg: 0,
@@ -324,6 +978,7 @@ impl ItemBuilder {
 			in_items: Vec::new(),
 			behind_items: Vec::new(),
 			under_items: Vec::new(),
+			attached_items: Vec::new(),
 		}
 	}
 }
\ No newline at end of file
@@ -2,21 +2,106 @@
 
 */
 
+use std::collections::BTreeMap;
 use std::fmt;
 use uuid::Uuid;
 
-use obj::item::Item;
-use obj::{ObjId,DamageType};
+use game::save::{SaveWriter,SaveReader};
+use obj::door::Door;
+use obj::item::{Item,ItemKey,remove_item_from,list_item_names,save_items,load_items};
+use obj::template::{self,Scope};
+use obj::ObjId;
 use parsing::DirectionType;
 use parsing::grammar::ItemNode;
 
-#[derive(Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Exit {
 	pub dest: usize,
 	pub desc: String,
 	pub travel_desc: Option<String>, // None gives default according to direction.
 	pub blocked_by: Option<ObjId>, // None means not blocked
 	pub blocked_desc: String,
+	pub door: Option<Door>, // Shared with the exit back the other way
+}
+
+impl Exit {
+	/*
+	Describes the door, if there is one, and what is blocking the exit,
+	if anything
+	*/
+	fn display_state(&self, blocked: bool) -> String {
+		let mut states = Vec::new();
+		if let Some(ref door) = self.door {
+			states.push(door.state_desc());
+		}
+		if blocked {
+			states.push(self.blocked_desc.clone());
+		}
+		states.join(" ")
+	}
+}
+
+impl Exit {
+	/*
+	Writes the exit in direction d to a save file
+	*/
+	fn save(&self, w: &mut SaveWriter, d: DirectionType) {
+		w.begin("exit");
+		w.value("direction", d.name());
+		w.value("dest", self.dest);
+		w.string("desc", &self.desc);
+		if let Some(ref s) = self.travel_desc {
+			w.string("travel_desc", s);
+		}
+		if let Some(ObjId::ITEMID(id)) = self.blocked_by {
+			w.value("blocked_by", id);
+		}
+		w.string("blocked_desc", &self.blocked_desc);
+		if let Some(ref door) = self.door {
+			door.save(w);
+		}
+		w.end();
+	}
+
+	/*
+	Reads an exit written by save, along with its direction. Destinations
+	must be one of the location_count locations in the save.
+	*/
+	fn load(r: &mut SaveReader, location_count: usize) -> Result<(DirectionType, Exit), String> {
+		r.begin("exit")?;
+		let name = r.string("direction")?;
+		let d = match DirectionType::from_name(&name) {
+			Some(d) => d,
+			None => return Err(format!("The save file is damaged: '{}' is not a direction.", name)),
+		};
+		let dest: usize = r.parse("dest")?;
+		if dest >= location_count {
+			return Err(format!("The save file is damaged: an exit leads to location {}, but there are only {}.", dest, location_count));
+		}
+		let desc = r.string("desc")?;
+		let travel_desc = match r.peek_key() {
+			Some("travel_desc") => Some(r.string("travel_desc")?),
+			_ => None,
+		};
+		let blocked_by = match r.peek_key() {
+			Some("blocked_by") => Some(ObjId::ITEMID(r.parse("blocked_by")?)),
+			_ => None,
+		};
+		let blocked_desc = r.string("blocked_desc")?;
+		let door = match r.peek_key() {
+			Some("begin") => Some(Door::load(r)?),
+			_ => None,
+		};
+		r.end()?;
+		Ok((d, Exit {
+			dest,
+			desc,
+			travel_desc,
+			blocked_by,
+			blocked_desc,
+			door,
+		}))
+	}
 }
 
 impl fmt::Display for Exit {
@@ -25,12 +110,20 @@ impl fmt::Display for Exit {
 	}
 }
 
+#[derive(Debug)]
 pub struct ExitBuilder {
 	dest: usize,
 	desc: Option<String>,
 	travel_desc: Option<String>,
 	blocked_by: Option<ObjId>,
 	blocked_desc: Option<String>,
+	door: Option<Door>,
+}
+
+impl Default for ExitBuilder {
+	fn default() -> ExitBuilder {
+		ExitBuilder::new()
+	}
 }
 
 impl ExitBuilder {
@@ -42,6 +135,7 @@ impl ExitBuilder {
 			travel_desc: None,
 			blocked_by: None,
 			blocked_desc: None,
+			door: None,
 		}
 	}
 
@@ -70,6 +164,15 @@ impl ExitBuilder {
 		self
 	}
 
+	/*
+	Puts a door in the exit. Give the exit back the other way a copy of
+	the same door.
+	*/
+	pub fn set_door(&mut self, door: &Door) -> &mut ExitBuilder {
+		self.door = Some(door.clone());
+		self
+	}
+
 	pub fn finalize(&self) -> Exit {
 		Exit {
 			dest: self.dest,
@@ -83,168 +186,218 @@ impl ExitBuilder {
 				Some(s) => s,
 				None => "You can't go that way.".to_string(),
 			},
+			door: self.door.clone(),
 		}
 	}
 }
 
-#[derive(Clone, PartialEq)]
-enum ExitExists {
-	YES(Exit),
-	NO(&'static str),
-}
-
-struct Exits {
-	pub n: ExitExists,
-	pub s: ExitExists,
-	pub e: ExitExists,
-	pub w: ExitExists,
-}
-
-impl Exits {
-	/*
-	Creates a new Exits struct with no exits using the no_exit &'static str
-	*/
-	pub fn new(no_exit: &'static str) -> Exits {
-		Exits {
-			n: ExitExists::NO(no_exit),
-			s: ExitExists::NO(no_exit),
-			e: ExitExists::NO(no_exit),
-			w: ExitExists::NO(no_exit),
-		}
-	}
-}
-
+#[derive(Debug, Clone)]
 pub struct Location {
 	pub name: String,
 	pub desc: String,
-	exits: Exits,
+	pub is_dark: bool, // Needs a light source to see in
+	pub points: i32, // Scored the first time the player enters
+	pub visited: bool, // The player has seen it, so any description now is a revisit
+	exits: BTreeMap<DirectionType, Exit>,
+	no_exit: String, // Shown when there is no exit in a direction
 	items: Vec<Item>,
 	scenery_items: Vec<Item>,
 }
 
+/*
+The parts of a location that can change as the game is played: its
+description, its doors and its items. Whether it has been visited is
+left out, as that only changes how it is described.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LocationKey {
+	desc: String,
+	doors: Vec<(Uuid, bool, bool)>, // Each door's id, and whether it is open and locked
+	items: Vec<ItemKey>,
+	scenery_items: Vec<ItemKey>,
+}
+
 impl Location {
 	/*
 	Creates a new Location from a name and desc.
 	The data members exits and items are empty to begin.
 	*/
-	pub fn new(n: &str, d: &str, no_exit: &'static str) -> Location {
+	pub fn new(n: &str, d: &str, no_exit: &str) -> Location {
 		Location {
 			name: n.to_string(),
 			desc: d.to_string(),
-			exits: Exits::new(no_exit),
+			is_dark: false,
+			points: 0,
+			visited: false,
+			exits: BTreeMap::new(),
+			no_exit: no_exit.to_string(),
 			items: Vec::new(),
 			scenery_items: Vec::new(),
 		}
 	}
 
-	pub fn update(&mut self, mut display: &mut String) {
+	/*
+	Returns the key of the location, used by GameState::state_key
+	*/
+	pub fn key(&self) -> LocationKey {
+		LocationKey {
+			desc: self.desc.clone(),
+			doors: self.doors().iter().map(|d| (d.get_id(), d.is_open, d.is_locked)).collect(),
+			items: self.items.iter().map(|i| i.key()).collect(),
+			scenery_items: self.scenery_items.iter().map(|i| i.key()).collect(),
+		}
+	}
+
+	/*
+	Writes the location, its exits and its items to a save file
+	*/
+	pub fn save(&self, w: &mut SaveWriter) {
+		w.begin("location");
+		w.string("name", &self.name);
+		w.string("desc", &self.desc);
+		w.value("is_dark", self.is_dark);
+		w.value("points", self.points);
+		w.value("was_visited", self.visited);
+		w.string("no_exit", &self.no_exit);
+		w.value("exit_count", self.exits.len());
+		for (d, e) in &self.exits {
+			e.save(w, *d);
+		}
+		save_items(w, "items", &self.items);
+		save_items(w, "scenery_items", &self.scenery_items);
+		w.end();
+	}
+
+	/*
+	Reads a location written by save
+	*/
+	pub fn load(r: &mut SaveReader, location_count: usize) -> Result<Location, String> {
+		r.begin("location")?;
+		let name = r.string("name")?;
+		let desc = r.string("desc")?;
+		let is_dark = r.parse("is_dark")?;
+		let points = r.parse("points")?;
+		let visited = r.parse("was_visited")?;
+		let no_exit = r.string("no_exit")?;
+		let exit_count: usize = r.parse("exit_count")?;
+		let mut exits = BTreeMap::new();
+		for _ in 0..exit_count {
+			let (d, e) = Exit::load(r, location_count)?;
+			exits.insert(d, e);
+		}
+		let location = Location {
+			name,
+			desc,
+			is_dark,
+			points,
+			visited,
+			exits,
+			no_exit,
+			items: load_items(r, "items")?,
+			scenery_items: load_items(r, "scenery_items")?,
+		};
+		r.end()?;
+		Ok(location)
+	}
+
+	pub fn update(&mut self, display: &mut String) {
 		for item in &mut self.items {
-			item.update(&mut display);
+			item.update(display);
+		}
+		for item in &mut self.scenery_items {
+			item.update(display);
+		}
+	}
+
+	/*
+	Returns true if anything in the location is giving off light
+	*/
+	pub fn has_light(&self) -> bool {
+		self.items.iter().chain(self.scenery_items.iter()).any(|i| i.has_light())
+	}
+
+	/*
+	Burns a turn of fuel from the light sources in the location
+	*/
+	pub fn burn_fuel(&mut self, display: &mut String, report: bool) {
+		for item in self.items.iter_mut().chain(self.scenery_items.iter_mut()) {
+			item.burn_fuel(display, report);
 		}
 	}
 
 	pub fn add_exit(&mut self, d: DirectionType, exit: Exit) {
-		let new_exit = ExitExists::YES(exit);
-		match d {
-			DirectionType::NORTH => self.exits.n = new_exit,
-			DirectionType::SOUTH => self.exits.s = new_exit,
-			DirectionType::EAST => self.exits.e = new_exit,
-			DirectionType::WEST => self.exits.w = new_exit,
-		};
+		self.exits.insert(d, exit);
+	}
+
+	/*
+	Returns the exit in direction d, if there is one
+	*/
+	pub fn exit(&self, d: DirectionType) -> Option<&Exit> {
+		self.exits.get(&d)
 	}
 
+	/*
+	Returns every exit with its direction, in the order they are listed
+	*/
+	pub fn exits(&self) -> Vec<(DirectionType, &Exit)> {
+		self.exits.iter().map(|(d, e)| (*d, e)).collect()
+	}
+
+	/*
+	Lists the exits, in the order of DIRECTIONS, with any door or blocker.
+	Exits without a desc aren't listed, which suits a second way of
+	saying the same thing, like "out" beside "west".
+	*/
 	pub fn display_exits(&self) -> String {
-		format!("{}{}{}{}", match self.exits.n {
-				ExitExists::YES(ref e) => format!("{} to the north. {}\n", e, {
-					if self.is_exit_blocked(e) {
-						e.blocked_desc.clone()
-					} else {
-						"".to_string()
-					}
-				}),
-				ExitExists::NO(_) => "".to_string(),
-			}, match self.exits.s {
-				ExitExists::YES(ref e) => format!("{} to the south. {}\n", e, {
-					if self.is_exit_blocked(e) {
-						e.blocked_desc.clone()
-					} else {
-						"".to_string()
-					}
-				}),
-				ExitExists::NO(_) => "".to_string(),
-			}, match self.exits.e {
-				ExitExists::YES(ref e) => format!("{} to the east. {}\n", e, {
-					if self.is_exit_blocked(e) {
-						e.blocked_desc.clone()
-					} else {
-						"".to_string()
-					}
-				}),
-				ExitExists::NO(_) => "".to_string(),
-			}, match self.exits.w {
-				ExitExists::YES(ref e) => format!("{} to the west. {}\n", e, {
-					if self.is_exit_blocked(e) {
-						e.blocked_desc.clone()
-					} else {
-						"".to_string()
-					}
-				}),
-				ExitExists::NO(_) => "".to_string(),
-			})
+		let mut display = String::new();
+		for (d, e) in self.exits.iter().filter(|&(_, e)| !e.desc.is_empty()) {
+			display.push_str(format!("{} {}. {}\n", e, d.whereabouts(), e.display_state(self.is_item_blocking(e))).as_str());
+		}
+		display
 	}
 
-	#[allow(unused_parens)]
 	pub fn find_exit(&self, d: DirectionType) -> Option<usize> {
-		match (match d {
-			DirectionType::NORTH => self.exits.n.clone(),
-			DirectionType::SOUTH => self.exits.s.clone(),
-			DirectionType::EAST => self.exits.e.clone(),
-			DirectionType::WEST => self.exits.w.clone(),
-		}) {
-			ExitExists::YES(ref e) => {
-				if self.is_exit_blocked(e) {
-					None
-				} else {
-					Some(e.dest)
-				}
-			},
-			ExitExists::NO(_) => None,
+		match self.exits.get(&d) {
+			Some(e) if !self.is_exit_blocked(e) => Some(e.dest),
+			_ => None,
 		}
 	}
 
 	/*
-	
+	Describes trying to go in direction d: the travel description if the
+	way is clear, otherwise what is in the way
 	*/
-	#[allow(unused_parens)]
 	pub fn display_exiting(&self, d: DirectionType) -> String {
-		format!("{}", match (match d {
-			DirectionType::NORTH => self.exits.n.clone(),
-			DirectionType::SOUTH => self.exits.s.clone(),
-			DirectionType::EAST => self.exits.e.clone(),
-			DirectionType::WEST => self.exits.w.clone(),
-		}) {
-			ExitExists::YES(ref e) => {
-				if self.is_exit_blocked(e) {
-					e.blocked_desc.clone()
-				} else {
-					Location::display_exiting_success(d, e.travel_desc.clone())
-				}
+		match self.exits.get(&d) {
+			Some(e) => match e.door {
+				Some(ref door) if !door.is_open => door.state_desc(),
+				_ => {
+					if self.is_item_blocking(e) {
+						e.blocked_desc.clone()
+					} else {
+						Location::display_exiting_success(d, e.travel_desc.clone())
+					}
+				},
 			},
-			ExitExists::NO(s) => s.to_string(),
-		})
+			None => self.no_exit.clone(),
+		}
 	}
 
+	/*
+	Returns true if a closed door or a blocking item is in the way
+	*/
 	fn is_exit_blocked(&self, e: &Exit) -> bool {
+		match e.door {
+			Some(ref door) if !door.is_open => true,
+			_ => self.is_item_blocking(e),
+		}
+	}
+
+	fn is_item_blocking(&self, e: &Exit) -> bool {
 		match e.blocked_by.clone() {
 			Some(obj) => match obj {
 				ObjId::ITEMID(id) => match self.find_item_by_id(id) {
-					Some(i) => {
-						if i.is_blocking() {
-							true
-						} else {
-							false
-						}
-					},
+					Some(i) => i.is_blocking(),
 					None => false,
 				},
 				// _ => false,
@@ -256,12 +409,30 @@ impl Location {
 	fn display_exiting_success(d: DirectionType, travel_desc: Option<String>) -> String {
 		match travel_desc {
 			Some(ref s) => s.clone(),
-			None => match d {
-				DirectionType::NORTH => "You go north.".to_string(),
-				DirectionType::SOUTH => "You go south.".to_string(),
-				DirectionType::EAST => "You go east.".to_string(),
-				DirectionType::WEST => "You go west.".to_string(),
-			},
+			None => format!("You go {}.", d.name()),
+		}
+	}
+
+	/*
+	Returns the doors in the location's exits
+	*/
+	pub fn doors(&self) -> Vec<&Door> {
+		self.exits.values().filter_map(|e| e.door.as_ref()).collect()
+	}
+
+	pub fn find_door(&self, i_node: &ItemNode) -> Option<Door> {
+		self.doors().into_iter().find(|d| d.matches(i_node)).cloned()
+	}
+
+	/*
+	Replaces any copy of the door in the location's exits with door
+	*/
+	pub fn update_door(&mut self, door: &Door) {
+		for e in self.exits.values_mut() {
+			match e.door {
+				Some(ref mut d) if d.get_id() == door.get_id() => *d = door.clone(),
+				_ => (),
+			}
 		}
 	}
 
@@ -273,19 +444,36 @@ impl Location {
 		}
 	}
 
+	/*
+	Returns every item in the location, including scenery and anything
+	held by other items, hidden or not
+	*/
+	pub fn all_items(&self) -> Vec<&Item> {
+		let mut items = Vec::new();
+		for item in self.items.iter().chain(self.scenery_items.iter()) {
+			items.extend(item.all_items());
+		}
+		items
+	}
+
+	/*
+	Returns the items in the location, not including scenery
+	*/
+	pub fn items(&self) -> &[Item] {
+		&self.items
+	}
+
 	pub fn find_item(&self, i_node: &ItemNode) -> Option<Item> {
-		match self.items.iter().position(|ref n| n.i_type == i_node.subject) {
+		match self.items.iter().position(|n| n.matches(i_node)) {
 			Some(i) => Some(self.items[i].clone()),
-			None => match self.scenery_items.iter().position(|ref n| n.i_type == i_node.subject) {
+			None => match self.scenery_items.iter().position(|n| n.matches(i_node)) {
 				Some(i) => Some(self.scenery_items[i].clone()),
 				None => {
 					let mut found_item: Option<Item> = None;
-					for item in &self.scenery_items {
-						for attached_item in &item.attached_items {
-							if attached_item.i_type == i_node.subject {
-								found_item = Some(attached_item.clone());
-								break;
-							}
+					for item in self.items.iter().chain(self.scenery_items.iter()) {
+						if let Some(i) = item.find_item(i_node) {
+							found_item = Some(i);
+							break;
 						}
 					}
 					found_item
@@ -294,85 +482,53 @@ impl Location {
 		}
 	}
 
+	/*
+	Same as find_item, but returns a mutable reference to the item.
+	*/
+	pub fn find_item_mut(&mut self, i_node: &ItemNode) -> Option<&mut Item> {
+		for item in self.items.iter_mut().chain(self.scenery_items.iter_mut()) {
+			if item.matches(i_node) {
+				return Some(item);
+			}
+			if let Some(i) = item.find_item_mut(i_node) {
+				return Some(i);
+			}
+		}
+		None
+	}
+
+	/*
+	Removes an item from the location, or from anything visible that
+	holds it. Scenery itself is never removed.
+	*/
 	pub fn remove_item(&mut self, i_node: &ItemNode) -> Option<Item> {
-		match self.items.iter().position(|ref n| n.i_type == i_node.subject) {
-			Some(i) => Some(self.items.remove(i)),
-			None => None,
+		match remove_item_from(&mut self.items, i_node) {
+			Some(i) => Some(i),
+			None => {
+				let mut found_item: Option<Item> = None;
+				for item in &mut self.scenery_items {
+					if let Some(i) = item.remove_item(i_node) {
+						found_item = Some(i);
+						break;
+					}
+				}
+				found_item
+			},
 		}
 	}
 
 	pub fn display_items(&self) -> String {
 		let mut display = String::new();
-		let mut something = false;
-		let n = self.items.len();
-		let mut i = 1;
 		for item in &self.scenery_items {
-			let n = item.attached_items.len();
-			i = 1;
-			if n > 0 {
-				something = true;
-				display.push_str("\nAttached to the ");
-				display.push_str(&item.name);
-				display.push_str(" is ");
-				for attached_item in &item.attached_items {
-					if i == n && i != 1 {
-						display.push_str("and ");
-					}
-					display.push_str(&attached_item.name);
-					if i == n {
-						display.push_str(".");
-					} else if n != 2 {
-						display.push_str(", ");
-					} else {
-						display.push_str(" ");
-					}
-					i += 1;
-				}
-			}
+			display.push_str(&item.display_contents());
 		}
-		if n > 0 {
-			something = true;
-			display.push_str("\nThere is ");
-			for item in &self.items {
-				if i == n && i != 1 {
-					display.push_str("and ");
-				}
-				display.push_str(&item.name);
-				if i == n {
-					display.push_str(" here.");
-				} else if n != 2 {
-					display.push_str(", ");
-				} else {
-					display.push_str(" ");
-				}
-				i += 1;
-			}
+		if !self.items.is_empty() {
+			display.push_str(format!("\nThere is {} here.", list_item_names(&self.items)).as_str());
 		}
 		for item in &self.items {
-			let n = item.attached_items.len();
-			i = 1;
-			if n > 0 {
-				something = true;
-				display.push_str("\nAttached to the ");
-				display.push_str(&item.name);
-				display.push_str(" is ");
-				for attached_item in &item.attached_items {
-					if i == n && i != 1 {
-						display.push_str("and ");
-					}
-					display.push_str(&attached_item.name);
-					if i == n {
-						display.push_str(".");
-					} else if n != 2 {
-						display.push_str(", ");
-					} else {
-						display.push_str(" ");
-					}
-					i += 1;
-				}
-			}
+			display.push_str(&item.display_contents());
 		}
-		if !something {
+		if display.is_empty() {
 			display.push_str("\nThere is nothing else of note that you can see here.");
 		}
 		display
@@ -381,48 +537,39 @@ impl Location {
 	pub fn find_item_by_id(&self, id: Uuid) -> Option<Item> {
 		let mut found_item: Option<Item> = None;
 		for item in &self.items {
-			match item.find_item_by_id(id) {
-				Some(i) => {
-					found_item = Some(i);
-					break;
-				},
-				None => (),
+			if let Some(i) = item.find_item_by_id(id) {
+				found_item = Some(i);
+				break;
 			}
 		};
 		for item in &self.scenery_items {
-			match item.find_item_by_id(id) {
-				Some(i) => {
-					found_item = Some(i);
-					break;
-				},
-				None => (),
+			if let Some(i) = item.find_item_by_id(id) {
+				found_item = Some(i);
+				break;
 			}
 		};
 		found_item
 	}
 
-	pub fn remove_first_item_by_damage_type(&mut self, damage_type: DamageType) -> Option<Item> {
-		match self.items.iter().position(|ref n| match n.damaged_by.clone() {
-			Some(d) => d == damage_type,
-			None => false,
-		}) {
-			Some(i) => Some(self.items.remove(i)),
-			None => None,
-		}
+	/*
+	Same as find_item_by_id, but returns a mutable reference to the item.
+	*/
+	pub fn find_item_by_id_mut(&mut self, id: Uuid) -> Option<&mut Item> {
+		self.items.iter_mut()
+			.chain(self.scenery_items.iter_mut())
+			.filter_map(|i| i.find_item_by_id_mut(id))
+			.next()
 	}
 
-	pub fn remove_first_attachable_item(&mut self) -> Option<Item> {
-		match self.items.iter().position(|ref n| n.can_attach) {
+	pub fn remove_attachable_item(&mut self, i_node: &ItemNode) -> Option<Item> {
+		match self.items.iter().position(|n| n.can_attach && n.matches(i_node)) {
 			Some(i) => Some(self.items.remove(i)),
 			None => {
 				let mut found_item: Option<Item> = None;
 				for item in &mut self.scenery_items {
-					match item.attached_items.iter().position(|ref n| n.can_attach) {
-						Some(i) => {
-							found_item = Some(item.attached_items.remove(i));
-							break;
-						},
-						None => (),
+					if let Some(i) = item.attached_items.iter().position(|n| n.can_attach && n.matches(i_node)) {
+						found_item = Some(item.attached_items.remove(i));
+						break;
 					}
 				};
 				found_item
@@ -433,6 +580,11 @@ impl Location {
 
 impl fmt::Display for Location {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "{}:\n{}\n{}{}", self.name, self.desc, self.display_exits(), self.display_items())
+		let scope = Scope {
+			here: Some(self.all_items()),
+			first: Some(!self.visited),
+			..Scope::default()
+		};
+		write!(f, "{}:\n{}\n{}{}", self.name, template::render(&self.desc, &scope), self.display_exits(), self.display_items())
 	}
 }
\ No newline at end of file
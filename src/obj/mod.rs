@@ -1,23 +1,42 @@
+pub mod door;
 pub mod item;
 pub mod location;
 pub mod player;
+pub mod template;
 
 use uuid::Uuid;
 
-#[derive(Clone, PartialEq)]
+use game::save::fingerprint;
+
+/*
+Makes an id from a name. The same name always gives the same id, so
+things loaded from a world file keep their ids from one run to the
+next, and a game saved in one run can be restored in another.
+*/
+pub fn id_from_name(name: &str) -> Uuid {
+	let mut bytes = [0u8; 16];
+	bytes[..8].copy_from_slice(&fingerprint(name).to_le_bytes());
+	bytes[8..].copy_from_slice(&fingerprint(&format!("{}\n", name)).to_le_bytes());
+	// Shaped like a version 4 Uuid
+	bytes[6] = (bytes[6] & 0x0f) | 0x40;
+	bytes[8] = (bytes[8] & 0x3f) | 0x80;
+	Uuid::from_bytes(&bytes).unwrap() // Always 16 bytes
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ObjId {
 	ITEMID(Uuid),
 	// LOCATIONID(Uuid),
 	// PLAYER,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DamageType {
 	SMASHING,
 	CUTTING,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DamageLevel {
 	NODAMAGE,
 	LIGHT,
@@ -1,8 +1,11 @@
 
 
-use obj::item::Item;
+use uuid::Uuid;
+
+use obj::item::{Item,remove_item_from};
 use parsing::grammar::ItemNode;
 
+#[derive(Debug, Clone)]
 pub struct Player {
 	pub inventory: Vec<Item>,
 	pub location: usize,
@@ -20,7 +23,7 @@ impl Player {
 	}
 
 	pub fn display_inventory(&self) -> String {
-		if self.inventory.len() == 0 {
+		if self.inventory.is_empty() {
 			"You are carrying nothing.".to_string()
 		} else {
 			let mut inventory = String::new();
@@ -28,26 +31,63 @@ impl Player {
 			inventory.push_str("You are carrying:");
 
 			for item in &self.inventory {
-				inventory.push_str("\n\t");
-				inventory.push_str(&item.name);
-				inventory.push_str("");
+				item.display_item_in_inventory(&mut inventory);
 			}
 			inventory
 		}
 	}
 
+	/*
+	Searches the inventory, including anything held by carried items
+	*/
 	pub fn find_item(&self, i_node: &ItemNode) -> Option<Item> {
-		match self.inventory.iter().position(|ref n| n.i_type == i_node.subject) {
+		match self.inventory.iter().position(|n| n.matches(i_node)) {
 			Some(i) => Some(self.inventory[i].clone()),
-			None => None,
+			None => {
+				let mut found_item: Option<Item> = None;
+				for item in &self.inventory {
+					if let Some(i) = item.find_item(i_node) {
+						found_item = Some(i);
+						break;
+					}
+				}
+				found_item
+			},
 		}
 	}
 
-	pub fn remove_item(&mut self, i_node: &ItemNode) -> Option<Item> {
-		match self.inventory.iter().position(|ref n| n.i_type == i_node.subject) {
-			Some(i) => Some(self.inventory.remove(i)),
-			None => None,
+	/*
+	Same as find_item, but returns a mutable reference to the item.
+	*/
+	pub fn find_item_mut(&mut self, i_node: &ItemNode) -> Option<&mut Item> {
+		for item in &mut self.inventory {
+			if item.matches(i_node) {
+				return Some(item);
+			}
+			if let Some(i) = item.find_item_mut(i_node) {
+				return Some(i);
+			}
 		}
+		None
+	}
+
+	/*
+	Searches the inventory, including anything held by carried items, for
+	the item with the given id
+	*/
+	pub fn find_item_by_id(&self, id: Uuid) -> Option<Item> {
+		self.inventory.iter().filter_map(|i| i.find_item_by_id(id)).next()
+	}
+
+	/*
+	Same as find_item_by_id, but returns a mutable reference to the item.
+	*/
+	pub fn find_item_by_id_mut(&mut self, id: Uuid) -> Option<&mut Item> {
+		self.inventory.iter_mut().filter_map(|i| i.find_item_by_id_mut(id)).next()
+	}
+
+	pub fn remove_item(&mut self, i_node: &ItemNode) -> Option<Item> {
+		remove_item_from(&mut self.inventory, i_node)
 	}
 
 	pub fn add_item(&mut self, item: Item) {
@@ -0,0 +1,406 @@
+/*
+Descriptions that change with the game.
+
+Location and item descriptions can hold tags in braces, which are worked
+out each time the description is shown:
+	{if CONDITION}text{end}
+	{if CONDITION}text{else}other text{end}
+	{a NOUN}, {the NOUN}: the item's name with the right article, or
+	{A NOUN} and {The NOUN} to start a sentence
+	{turns}: the number of turns taken
+	{{ and }}: a brace
+A condition can start with "not", and is one of:
+	first: the location is being described to the player for the first time
+	flag NAME: the flag is set
+	turns N: at least N turns have passed
+	open NOUN, on NOUN, damaged NOUN, destroyed NOUN: the item's state
+	here NOUN: the item is in the location
+	held NOUN: the player is carrying the item
+NOUN is an item's type or alias, or "it" for the item being described.
+Ifs can be nested.
+
+Descriptions are rendered by the Display impls of Location and Item.
+The game's turns, flags and surroundings aren't part of either, so the
+GameState sets them with with_context while it formats what the player
+sees, and everything the player sees should be formatted with
+GameState::render. Rendering a description with tags outside a context
+is a mistake, which debug builds stop at; otherwise the game is taken to
+be on its first turn, with no flags set and nothing carried. A
+description with a mistake in it is shown as it is written; check finds
+the mistake.
+*/
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use obj::DamageLevel;
+use obj::item::Item;
+
+/*
+What a description can depend on beyond the thing it describes:
+	turns: the number of turns taken
+	flags: the flags that are set
+	first: true if the location hasn't been described to the player before
+	here: the items in the location, including those inside others
+	held: the items the player carries, including those inside others
+*/
+#[derive(Debug, Clone)]
+pub struct Context {
+	pub turns: u32,
+	pub flags: HashSet<String>,
+	pub first: bool,
+	pub here: Vec<Seen>,
+	pub held: Vec<Seen>,
+}
+
+/*
+What a description can find out about an item it mentions: the nouns
+it goes by, its name and its state
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Seen {
+	nouns: Vec<String>,
+	name: String,
+	is_open: bool,
+	is_on: bool,
+	damage: DamageLevel,
+}
+
+impl Seen {
+	pub fn new(item: &Item) -> Seen {
+		let mut nouns = vec![item.i_type.clone()];
+		nouns.extend(item.aliases.iter().cloned());
+		Seen {
+			nouns,
+			name: item.name.clone(),
+			is_open: item.is_open,
+			is_on: item.is_on(),
+			damage: item.damage_level(),
+		}
+	}
+
+	fn is_called(&self, noun: &str) -> bool {
+		self.nouns.iter().any(|n| n == noun)
+	}
+}
+
+impl Default for Context {
+	fn default() -> Context {
+		Context {
+			turns: 0,
+			flags: HashSet::new(),
+			first: true,
+			here: Vec::new(),
+			held: Vec::new(),
+		}
+	}
+}
+
+/*
+What the thing being described knows about itself:
+	this: the item being described
+	here: the location's items, when a location is being described
+	first: whether this is the first visit, when a location is being
+	described
+Anything it doesn't know comes from the context.
+*/
+#[derive(Debug, Default)]
+pub struct Scope<'a> {
+	pub this: Option<&'a Item>,
+	pub here: Option<Vec<&'a Item>>,
+	pub first: Option<bool>,
+}
+
+thread_local! {
+	static CONTEXT: RefCell<Option<Context>> = const { RefCell::new(None) };
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+	TEXT(String),
+	NAME(String, bool, bool), // The noun, whether the article is definite, and whether to capitalize
+	TURNS,
+	IF(Test, Vec<Node>, Vec<Node>), // What to show if the test passes, and if not
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Test {
+	negated: bool,
+	check: Check,
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq)]
+enum Check {
+	FIRST,
+	FLAG(String),
+	TURNS(u32),
+	OPEN(String),
+	ON(String),
+	DAMAGED(String),
+	DESTROYED(String),
+	HERE(String),
+	HELD(String),
+}
+
+/*
+Puts back the context that was set before with_context when dropped,
+so it is put back even if f panics
+*/
+struct Restore(Option<Context>);
+
+impl Drop for Restore {
+	fn drop(&mut self) {
+		let old = self.0.take();
+		CONTEXT.with(|c| *c.borrow_mut() = old);
+	}
+}
+
+/*
+Runs f with context set for any descriptions it renders
+*/
+pub fn with_context<T, F: FnOnce() -> T>(context: Context, f: F) -> T {
+	let _restore = Restore(CONTEXT.with(|c| c.replace(Some(context))));
+	f()
+}
+
+/*
+Renders a description for the thing in scope. A description with a
+mistake in it is returned as it is.
+*/
+pub fn render(text: &str, scope: &Scope) -> String {
+	if !text.contains('{') && !text.contains('}') {
+		return text.to_string();
+	}
+	let nodes = match parse(text) {
+		Ok(n) => n,
+		Err(_) => return text.to_string(),
+	};
+	CONTEXT.with(|c| {
+		let context = c.borrow();
+		debug_assert!(context.is_some(), "'{}' was rendered outside with_context; format it with GameState::render", text);
+		let default = Context::default();
+		let context = context.as_ref().unwrap_or(&default);
+		let mut out = String::new();
+		write_nodes(&nodes, scope, context, &mut out);
+		out
+	})
+}
+
+/*
+Returns the first mistake in a description, if it has one
+*/
+pub fn check(text: &str) -> Result<(), String> {
+	parse(text).map(|_| ())
+}
+
+/*
+Puts an article in front of an item's name. Names are written with
+their article ("a lamp", "an egg", "some coins"), which is swapped for
+"the" when definite is true. Names without one, like "Professor Ames"
+or "your guide", are left as they are.
+*/
+pub fn with_article(name: &str, definite: bool) -> String {
+	let (article, bare) = match name.find(' ') {
+		Some(i) if ["a", "an", "the", "some"].contains(&&name[..i]) => (&name[..i], &name[i + 1..]),
+		_ => return name.to_string(),
+	};
+	if definite {
+		format!("the {}", bare)
+	} else if article == "some" {
+		name.to_string()
+	} else {
+		format!("{} {}", indefinite_article(bare), bare)
+	}
+}
+
+fn indefinite_article(word: &str) -> &'static str {
+	match word.chars().next() {
+		Some(c) if "aeiouAEIOU".contains(c) => "an",
+		_ => "a",
+	}
+}
+
+fn write_nodes(nodes: &[Node], scope: &Scope, context: &Context, out: &mut String) {
+	for node in nodes {
+		match *node {
+			Node::TEXT(ref t) => out.push_str(t),
+			Node::TURNS => out.push_str(&context.turns.to_string()),
+			Node::NAME(ref noun, definite, capital) => {
+				let name = match find(noun, scope, context) {
+					Some(item) => with_article(&item.name, definite),
+					None if definite => format!("the {}", noun),
+					None => format!("{} {}", indefinite_article(noun), noun),
+				};
+				if capital {
+					out.push_str(&capitalize(&name));
+				} else {
+					out.push_str(&name);
+				}
+			},
+			Node::IF(ref test, ref then, ref otherwise) => {
+				if passes(test, scope, context) {
+					write_nodes(then, scope, context, out);
+				} else {
+					write_nodes(otherwise, scope, context, out);
+				}
+			},
+		}
+	}
+}
+
+fn passes(test: &Test, scope: &Scope, context: &Context) -> bool {
+	let state = |noun: &str, f: &dyn Fn(&Seen) -> bool| find(noun, scope, context).as_ref().is_some_and(f);
+	let result = match test.check {
+		Check::FIRST => scope.first.unwrap_or(context.first),
+		Check::FLAG(ref f) => context.flags.contains(f),
+		Check::TURNS(n) => context.turns >= n,
+		Check::OPEN(ref noun) => state(noun, &|i| i.is_open),
+		Check::ON(ref noun) => state(noun, &|i| i.is_on),
+		Check::DAMAGED(ref noun) => state(noun, &|i| i.damage != DamageLevel::NODAMAGE),
+		Check::DESTROYED(ref noun) => state(noun, &|i| i.damage == DamageLevel::DESTROYED),
+		Check::HERE(ref noun) => match scope.here {
+			Some(ref items) => items.iter().any(|i| is_called(i, noun)),
+			None => context.here.iter().any(|i| i.is_called(noun)),
+		},
+		Check::HELD(ref noun) => context.held.iter().any(|i| i.is_called(noun)),
+	};
+	result != test.negated
+}
+
+/*
+Finds the item a noun refers to: the item being described, then the
+location's items, then the player's
+*/
+fn find(noun: &str, scope: &Scope, context: &Context) -> Option<Seen> {
+	if noun == "it" {
+		return scope.this.map(Seen::new);
+	}
+	let here = match scope.here {
+		Some(ref items) => items.iter().find(|i| is_called(i, noun)).map(|i| Seen::new(i)),
+		None => context.here.iter().find(|i| i.is_called(noun)).cloned(),
+	};
+	here.or_else(|| context.held.iter().find(|i| i.is_called(noun)).cloned())
+}
+
+fn is_called(item: &Item, noun: &str) -> bool {
+	item.i_type == noun || item.aliases.iter().any(|a| a == noun)
+}
+
+fn capitalize(s: &str) -> String {
+	let mut chars = s.chars();
+	match chars.next() {
+		Some(c) => c.to_uppercase().chain(chars).collect(),
+		None => String::new(),
+	}
+}
+
+/*
+Parses a description into text and tags. Ifs are gathered up with the
+text they control, so the result is a tree.
+*/
+fn parse(text: &str) -> Result<Vec<Node>, String> {
+	// Each open if, with its test, its nodes so far, and its then nodes
+	// once {else} has been seen
+	let mut open: Vec<(Test, Vec<Node>, Option<Vec<Node>>)> = Vec::new();
+	let mut nodes: Vec<Node> = Vec::new();
+	let mut rest = text;
+	loop {
+		let next = rest.find(['{', '}']);
+		let (plain, tail) = match next {
+			Some(i) => (&rest[..i], &rest[i..]),
+			None => (rest, ""),
+		};
+		push_text(&mut nodes, plain);
+		if tail.is_empty() {
+			break;
+		}
+		if tail.starts_with("{{") || tail.starts_with("}}") {
+			push_text(&mut nodes, &tail[..1]);
+			rest = &tail[2..];
+			continue;
+		}
+		if tail.starts_with('}') {
+			return Err("a } has no { before it (write }} for a brace)".to_string());
+		}
+		let end = match tail.find('}') {
+			Some(e) => e,
+			None => return Err("a { is never closed (write {{ for a brace)".to_string()),
+		};
+		let tag = tail[1..end].trim();
+		rest = &tail[end + 1..];
+		let words: Vec<&str> = tag.split_whitespace().collect();
+		match words.as_slice() {
+			["if", condition @ ..] => {
+				let test = parse_test(condition, tag)?;
+				open.push((test, nodes, None));
+				nodes = Vec::new();
+			},
+			["else"] => match open.last_mut() {
+				Some(&mut (_, _, ref mut then @ None)) => *then = Some(std::mem::take(&mut nodes)),
+				Some(_) => return Err("an {if} has two {else}s".to_string()),
+				None => return Err("{else} has no {if} before it".to_string()),
+			},
+			["end"] => match open.pop() {
+				Some((test, outer, then)) => {
+					let node = match then {
+						Some(then) => Node::IF(test, then, nodes),
+						None => Node::IF(test, nodes, Vec::new()),
+					};
+					nodes = outer;
+					nodes.push(node);
+				},
+				None => return Err("{end} has no {if} before it".to_string()),
+			},
+			["turns"] => nodes.push(Node::TURNS),
+			[article, noun] if ["a", "an", "the", "A", "An", "The"].contains(article) => {
+				let definite = article.eq_ignore_ascii_case("the");
+				let capital = article.starts_with(char::is_uppercase);
+				nodes.push(Node::NAME(noun.to_string(), definite, capital));
+			},
+			_ => return Err(format!("{{{}}} isn't a tag", tag)),
+		}
+	}
+	match open.last() {
+		Some(_) => Err("an {if} has no {end}".to_string()),
+		None => Ok(nodes),
+	}
+}
+
+fn push_text(nodes: &mut Vec<Node>, text: &str) {
+	if text.is_empty() {
+		return;
+	}
+	if let Some(&mut Node::TEXT(ref mut t)) = nodes.last_mut() {
+		t.push_str(text);
+		return;
+	}
+	nodes.push(Node::TEXT(text.to_string()));
+}
+
+fn parse_test(words: &[&str], tag: &str) -> Result<Test, String> {
+	let (negated, words) = match words.split_first() {
+		Some((&"not", rest)) => (true, rest),
+		_ => (false, words),
+	};
+	let check = match words {
+		["first"] => Check::FIRST,
+		["flag", name] => Check::FLAG(name.to_string()),
+		["turns", n] => match n.parse() {
+			Ok(n) => Check::TURNS(n),
+			Err(_) => return Err(format!("{{{}}} needs a number of turns", tag)),
+		},
+		["open", noun] => Check::OPEN(noun.to_string()),
+		["on", noun] => Check::ON(noun.to_string()),
+		["damaged", noun] => Check::DAMAGED(noun.to_string()),
+		["destroyed", noun] => Check::DESTROYED(noun.to_string()),
+		["here", noun] => Check::HERE(noun.to_string()),
+		["held", noun] => Check::HELD(noun.to_string()),
+		_ => return Err(format!("{{{}}} doesn't have a condition it understands", tag)),
+	};
+	Ok(Test {
+		negated,
+		check,
+	})
+}
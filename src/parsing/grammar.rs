@@ -2,32 +2,16 @@
 
 */
 
-use parsing::{GameStateType,PrepositionType,DirectionType,OtherType};
+use obj::item::Item;
+use parsing::PrepositionType;
 
-#[derive(Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ItemNode {
-	// pub desc: DescType,
+	pub adjectives: Vec<String>,
 	pub subject: String,
 	pub subject_lexeme: String,
 }
 
-/*
-FOR verbs like put or break later on
-pub struct ItemNode {
-	pub desc: DescType,
-	pub subject: String,
-	pub preposition: PrepositionType,
-	pub obj_desc: DescType,
-	pub object: String,
-}
-*/
-
-/*
-FOR TAKE/DROP later on
-pub struct ItemListNode {
-	// Vector of ItemNodes
-}
-*/
 
 impl ItemNode {
 	/*
@@ -35,13 +19,74 @@ impl ItemNode {
 	*/
 	pub fn new(item_type: &str, item_lexeme: &str) -> ItemNode {
 		ItemNode {
+			adjectives: Vec::new(),
 			subject: item_type.to_string(),
 			subject_lexeme: item_lexeme.to_string(),
 		}
 	}
+
+	/*
+	Creates a new ItemNode that refers to an existing Item.
+	*/
+	pub fn from_item(item: &Item) -> ItemNode {
+		ItemNode::new(item.i_type.as_str(), item.i_type.as_str())
+	}
+}
+
+/*
+For verbs that can act on several items at once, like take or drop.
+	take [item] and [item]
+	drop all except [item]
+	take [item] from [item]
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemListNode {
+	pub all: bool,
+	pub items: Vec<ItemNode>,
+	pub except: Vec<ItemNode>,
+	pub from: Option<ItemNode>,
 }
 
-#[derive(Clone, PartialEq)]
+impl Default for ItemListNode {
+	fn default() -> ItemListNode {
+		ItemListNode::new()
+	}
+}
+
+impl ItemListNode {
+	/*
+	Creates a new, empty ItemListNode.
+	*/
+	pub fn new() -> ItemListNode {
+		ItemListNode {
+			all: false,
+			items: Vec::new(),
+			except: Vec::new(),
+			from: None,
+		}
+	}
+
+	/*
+	Returns the ItemNode if exactly one item was named, without all or
+	except. The from item is not considered.
+	*/
+	pub fn single(&self) -> Option<&ItemNode> {
+		if !self.all && self.items.len() == 1 && self.except.is_empty() {
+			self.items.first()
+		} else {
+			None
+		}
+	}
+
+	/*
+	Returns true if the ItemNode was excluded with except.
+	*/
+	pub fn is_excepted(&self, i_node: &ItemNode) -> bool {
+		self.except.iter().any(|n| n.subject == i_node.subject)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct PrepositionNode {
 	pub prep: PrepositionType,
 	pub item: ItemNode,
@@ -57,4 +102,30 @@ impl PrepositionNode {
 			item: i,
 		}
 	}
+}
+
+/*
+For verbs that act on one item using another, like cut, hit or tie.
+	cut [subject] with [object]
+	tie [subject] to [object]
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemToItemNode {
+	pub subject: ItemNode,
+	pub prep: PrepositionType,
+	pub object: ItemNode,
+}
+
+impl ItemToItemNode {
+	/*
+	Creates a new ItemToItemNode using a subject ItemNode, a PrepositionType
+	and an object ItemNode.
+	*/
+	pub fn new(s: ItemNode, p: PrepositionType, o: ItemNode) -> ItemToItemNode {
+		ItemToItemNode {
+			subject: s,
+			prep: p,
+			object: o,
+		}
+	}
 }
\ No newline at end of file
@@ -0,0 +1,90 @@
+/*
+The item vocabulary of a world, built from the items themselves so that
+adding an item to a world doesn't need parser edits.
+*/
+
+use std::collections::{HashMap,HashSet};
+
+use obj::door::Door;
+use obj::item::Item;
+use obj::location::Location;
+
+#[derive(Debug, Clone)]
+pub struct Lexicon {
+	nouns: HashMap<String, String>,
+	adjectives: HashSet<String>,
+}
+
+impl Default for Lexicon {
+	fn default() -> Lexicon {
+		Lexicon::new()
+	}
+}
+
+impl Lexicon {
+	/*
+	Creates an empty Lexicon.
+	*/
+	pub fn new() -> Lexicon {
+		Lexicon {
+			nouns: HashMap::new(),
+			adjectives: HashSet::new(),
+		}
+	}
+
+	/*
+	Creates a Lexicon from every item in the locations, including
+	hidden and nested items, and every door.
+	*/
+	pub fn from_locations(locations: &[Location]) -> Lexicon {
+		let mut lexicon = Lexicon::new();
+		for location in locations {
+			for item in location.all_items() {
+				lexicon.add_item(item);
+			}
+			for door in location.doors() {
+				lexicon.add_door(door);
+			}
+		}
+		lexicon
+	}
+
+	/*
+	Adds an item's type and aliases as nouns, and its adjectives (which
+	include the other words of its name) as adjectives. The first item to
+	claim a noun keeps it.
+	*/
+	pub fn add_item(&mut self, item: &Item) {
+		self.nouns.entry(item.i_type.to_lowercase()).or_insert_with(|| item.i_type.clone());
+		for alias in &item.aliases {
+			self.nouns.entry(alias.to_lowercase()).or_insert_with(|| item.i_type.clone());
+		}
+		for adjective in item.all_adjectives() {
+			self.adjectives.insert(adjective);
+		}
+	}
+
+	/*
+	Adds a door's words, the same way as an item's
+	*/
+	pub fn add_door(&mut self, door: &Door) {
+		self.nouns.entry(door.i_type.to_lowercase()).or_insert_with(|| door.i_type.clone());
+		for alias in &door.aliases {
+			self.nouns.entry(alias.to_lowercase()).or_insert_with(|| door.i_type.clone());
+		}
+		for adjective in door.all_adjectives() {
+			self.adjectives.insert(adjective);
+		}
+	}
+
+	/*
+	Returns the item type a noun refers to
+	*/
+	pub fn noun(&self, word: &str) -> Option<&str> {
+		self.nouns.get(word).map(|s| s.as_str())
+	}
+
+	pub fn is_adjective(&self, word: &str) -> bool {
+		self.adjectives.contains(word)
+	}
+}
@@ -1,8 +1,9 @@
 pub mod grammar;
+pub mod lexicon;
 pub mod parser;
 pub mod token;
 
-use parsing::grammar::{ItemNode,PrepositionNode};
+use parsing::grammar::{ItemNode,ItemListNode,PrepositionNode,ItemToItemNode};
 
 /*
 Enums for Token:
@@ -10,49 +11,129 @@ Enums for Token:
 	GameStateType: for quit, save, and other game state commands
 	VerbType: for verbs
 	PrepositionType: for prepositions
-	DirectionType: for the cardinal directions
+	DirectionType: for the compass directions, up, down, in and out
+	ListType: for words that build lists of items
 	OtherType: any other types
 */
 
-#[derive(Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
 	GAMESTATE(GameStateType),
 	VERB(VerbType),
 	PREPOSITION(PrepositionType),
 	DIRECTION(DirectionType),
+	LIST(ListType),
 	ITEM(String),
+	ADJECTIVE,
 	OTHER(OtherType),
 	WORD, BAD,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameStateType {
-	QUIT,
+	QUIT, SAVE, RESTORE, UNDO, RESTART, SCORE, SEED,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum VerbType {
 	// Location
-	GO, LOOK,
+	GO, LOOK, JUMP,
 	// Inventory
 	INVENTORY, TAKE, DROP, EXAMINE,
 	// Basic Item
-	USE,
+	USE, OPEN, CLOSE, LOCK, UNLOCK, TURN, LIGHT, EXTINGUISH, CLIMB,
 	// Item to Item
-	CUT, HIT, TIE,
+	CUT, HIT, TIE, PUT,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PrepositionType {
-	AT, ON, IN, WITH, UNDER, BEHIND, TO,
+	AT, ON, IN, WITH, UNDER, BEHIND, TO, FROM, OVER, OFF,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum DirectionType {
 	NORTH, SOUTH, EAST, WEST,
+	NORTHEAST, NORTHWEST, SOUTHEAST, SOUTHWEST,
+	UP, DOWN, IN, OUT,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+/*
+Every direction, in the order exits are listed
+*/
+pub const DIRECTIONS: [DirectionType; 12] = [
+	DirectionType::NORTH, DirectionType::SOUTH, DirectionType::EAST, DirectionType::WEST,
+	DirectionType::NORTHEAST, DirectionType::NORTHWEST, DirectionType::SOUTHEAST, DirectionType::SOUTHWEST,
+	DirectionType::UP, DirectionType::DOWN, DirectionType::IN, DirectionType::OUT,
+];
+
+impl DirectionType {
+	/*
+	Returns the full name of the direction, as used in world and save
+	files
+	*/
+	pub fn name(self) -> &'static str {
+		match self {
+			DirectionType::NORTH => "north",
+			DirectionType::SOUTH => "south",
+			DirectionType::EAST => "east",
+			DirectionType::WEST => "west",
+			DirectionType::NORTHEAST => "northeast",
+			DirectionType::NORTHWEST => "northwest",
+			DirectionType::SOUTHEAST => "southeast",
+			DirectionType::SOUTHWEST => "southwest",
+			DirectionType::UP => "up",
+			DirectionType::DOWN => "down",
+			DirectionType::IN => "in",
+			DirectionType::OUT => "out",
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<DirectionType> {
+		DIRECTIONS.iter().find(|d| d.name() == name).cloned()
+	}
+
+	/*
+	Describes where an exit in this direction is, as in "A door lies to
+	the north"
+	*/
+	pub fn whereabouts(self) -> String {
+		match self {
+			DirectionType::UP => "above".to_string(),
+			DirectionType::DOWN => "below".to_string(),
+			DirectionType::IN => "inside".to_string(),
+			DirectionType::OUT => "outside".to_string(),
+			d => format!("to the {}", d.name()),
+		}
+	}
+
+	/*
+	Returns the direction that leads back the way this one came
+	*/
+	pub fn opposite(self) -> DirectionType {
+		match self {
+			DirectionType::NORTH => DirectionType::SOUTH,
+			DirectionType::SOUTH => DirectionType::NORTH,
+			DirectionType::EAST => DirectionType::WEST,
+			DirectionType::WEST => DirectionType::EAST,
+			DirectionType::NORTHEAST => DirectionType::SOUTHWEST,
+			DirectionType::NORTHWEST => DirectionType::SOUTHEAST,
+			DirectionType::SOUTHEAST => DirectionType::NORTHWEST,
+			DirectionType::SOUTHWEST => DirectionType::NORTHEAST,
+			DirectionType::UP => DirectionType::DOWN,
+			DirectionType::DOWN => DirectionType::UP,
+			DirectionType::IN => DirectionType::OUT,
+			DirectionType::OUT => DirectionType::IN,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListType {
+	ALL, AND, EXCEPT,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum OtherType {
 	YES, NO,
 }
@@ -61,18 +142,24 @@ pub enum OtherType {
 Enum for Commands
 */
 
-#[derive(Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CommandNode {
 	// Singleton Commands
 	LOOK(Option<PrepositionNode>), INVENTORY,
 	// Direction Commands
-	GO(DirectionType),
+	GO(DirectionType), JUMP(Option<ItemNode>),
 	// Item Commands
-	TAKE(ItemNode), DROP(ItemNode),
+	TAKE(ItemListNode), DROP(ItemListNode),
 	EXAMINE(ItemNode), USE(ItemNode),
+	OPEN(ItemNode), CLOSE(ItemNode),
+	LOCK(ItemNode, Option<ItemNode>), UNLOCK(ItemNode, Option<ItemNode>), // With an optional key
+	TURNON(ItemNode), TURNOFF(ItemNode),
+	// Item to Item Commands
+	CUT(ItemToItemNode), HIT(ItemToItemNode), TIE(ItemToItemNode),
+	PUT(ItemToItemNode),
 	// Other Commands
 	// BAD(String),
-	GAMESTATE(GameStateType),
+	GAMESTATE(GameStateType, Option<String>), // File name for save and restore
 	OTHER(OtherType),
 	//ERROR(String),
 }
\ No newline at end of file
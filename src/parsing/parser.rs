@@ -2,20 +2,19 @@
 
 */
 
-use std::io;
-
-use parsing::{TokenType,GameStateType,VerbType,OtherType,CommandNode};
+use parsing::{TokenType,GameStateType,VerbType,PrepositionType,DirectionType,ListType,OtherType,CommandNode};
 use parsing::token::Token;
-use parsing::grammar::{ItemNode,PrepositionNode};
+use parsing::lexicon::Lexicon;
+use parsing::grammar::{ItemNode,ItemListNode,PrepositionNode,ItemToItemNode};
 
 /*
 If a Token is still in input, it is removed from input and returned
 */
-fn get_token(input: &mut Vec<&str>) -> Option<Token> {
-	if input.len() == 0 {
+fn get_token(input: &mut Vec<Token>) -> Option<Token> {
+	if input.is_empty() {
 		None
 	} else {
-		Some(Token::new(input.remove(0)))
+		Some(input.remove(0))
 	}
 }
 
@@ -23,50 +22,37 @@ fn get_token(input: &mut Vec<&str>) -> Option<Token> {
 If a Token is still in input, it is returned, leaving the input
 unchanged
 */
-#[allow(dead_code)]
-fn peek_token(input: &Vec<&str>) -> Option<Token> {
-	if input.len() == 0 {
-		None
-	} else {
-		Some(Token::new(input[0]))
-	}
+fn peek_token(input: &[Token]) -> Option<&Token> {
+	input.first()
 }
 
 /*
-If a Token is still in input, return the lexeme, leaving the input
-unchanged
+Splits a line of input into Tokens, using the lexicon for item words,
+and parses it. A save file name is kept as it was typed.
 */
-fn peek_lexeme<'a>(input: &Vec<&'a str>) -> &'a str {
-	if input.len() == 0 {
-		""
-	} else {
-		input[0]
+pub fn parse_line(line: &str, lexicon: &Lexicon) -> Option<CommandNode> {
+	let lower = line.to_lowercase().replace(",", " , ");
+	let mut tokens = lower.split_whitespace()
+		.map(|w| Token::with_lexicon(w, lexicon))
+		.collect::<Vec<Token>>();
+	match parse(&mut tokens) {
+		Some(CommandNode::GAMESTATE(s, Some(_))) => Some(CommandNode::GAMESTATE(s, line.split_whitespace().nth(1).map(String::from))),
+		command => command,
 	}
 }
 
-/*
-Retrieves the next input by the user and parses it, returning
-the parse tree
-*/
-pub fn get_next_command() -> Option<CommandNode> {
-	let mut input = String::new();
-	io::stdin().read_line(&mut input)
-			.expect("Failed to read line");
-
-	let mut words = input.split_whitespace().collect::<Vec<&str>>();
-	parse(&mut words)
-}
-
 /*
 Parses a given command (the input Vec) into a parse tree
 */
-fn parse(input: &mut Vec<&str>) -> Option<CommandNode> {
+fn parse(input: &mut Vec<Token>) -> Option<CommandNode> {
 	match get_token(input) {
 		Some(next_token) => match next_token.t_type {
 			TokenType::GAMESTATE(s) => gamestate(s, input),
 			TokenType::VERB(v) => verb(v, input),
 			TokenType::OTHER(o) => other(o, input),
 			TokenType::DIRECTION(d) => Some(CommandNode::GO(d)),
+			// A bare "in" means go in
+			TokenType::PREPOSITION(PrepositionType::IN) if input.is_empty() => Some(CommandNode::GO(DirectionType::IN)),
 			_ => None,
 		},
 		None => None,
@@ -74,50 +60,125 @@ fn parse(input: &mut Vec<&str>) -> Option<CommandNode> {
 }
 
 /*
-Determines the game state command
+Determines the game state command. Save and restore may be followed by
+a file name.
 */
-#[allow(unused_variables)]
-fn gamestate(s: GameStateType, input: &mut Vec<&str>) -> Option<CommandNode> {
+fn gamestate(s: GameStateType, input: &mut Vec<Token>) -> Option<CommandNode> {
 	match s {
-		GameStateType::QUIT => Some(CommandNode::GAMESTATE(s)),
+		GameStateType::QUIT | GameStateType::UNDO | GameStateType::RESTART | GameStateType::SCORE | GameStateType::SEED => Some(CommandNode::GAMESTATE(s, None)),
+		GameStateType::SAVE | GameStateType::RESTORE => match get_token(input) {
+			Some(t) => Some(CommandNode::GAMESTATE(s, Some(t.lexeme))),
+			None => Some(CommandNode::GAMESTATE(s, None)),
+		},
 	}
 }
 
 /*
 Determines the verb of the command
 */
-fn verb(v: VerbType, input: &mut Vec<&str>) -> Option<CommandNode> {
+fn verb(v: VerbType, input: &mut Vec<Token>) -> Option<CommandNode> {
 	match v {
 		VerbType::LOOK => Some(CommandNode::LOOK(preposition(input))),
 		VerbType::INVENTORY => Some(CommandNode::INVENTORY),
 		VerbType::GO => go(input),
-		VerbType::TAKE => match item(input) {
-			Some(i) => Some(CommandNode::TAKE(i)),
-			None => None,
-		},
-		VerbType::DROP => match item(input) {
-			Some(i) => Some(CommandNode::DROP(i)),
-			None => None,
+		VerbType::CLIMB => if input.is_empty() {
+			Some(CommandNode::GO(DirectionType::UP))
+		} else {
+			go(input)
 		},
-		VerbType::EXAMINE => match item(input) {
-			Some(i) => Some(CommandNode::EXAMINE(i)),
+		VerbType::JUMP => jump(input),
+		VerbType::TAKE => match item_list(input) {
+			Some(mut i) => match preposition(input) {
+				Some(ref p_node) if p_node.prep == PrepositionType::FROM => {
+					i.from = Some(p_node.item.clone());
+					Some(CommandNode::TAKE(i))
+				},
+				Some(_) => None,
+				None => Some(CommandNode::TAKE(i)),
+			},
 			None => None,
 		},
-		VerbType::USE => match item(input) {
-			Some(i) => Some(CommandNode::USE(i)),
-			None => None,
+		VerbType::DROP => item_list(input).map(CommandNode::DROP),
+		VerbType::EXAMINE => item(input).map(CommandNode::EXAMINE),
+		VerbType::USE => item(input).map(CommandNode::USE),
+		VerbType::OPEN => item(input).map(CommandNode::OPEN),
+		VerbType::CLOSE => item(input).map(CommandNode::CLOSE),
+		VerbType::LOCK => lock(input).map(|(i, key)| CommandNode::LOCK(i, key)),
+		VerbType::UNLOCK => lock(input).map(|(i, key)| CommandNode::UNLOCK(i, key)),
+		VerbType::TURN => turn(input),
+		VerbType::LIGHT => item(input).map(CommandNode::TURNON),
+		VerbType::EXTINGUISH => item(input).map(CommandNode::TURNOFF),
+		VerbType::CUT => item_to_item(input, &[PrepositionType::WITH]).map(CommandNode::CUT),
+		VerbType::HIT => item_to_item(input, &[PrepositionType::WITH]).map(CommandNode::HIT),
+		VerbType::TIE => item_to_item(input, &[PrepositionType::TO, PrepositionType::ON]).map(CommandNode::TIE),
+		VerbType::PUT => item_to_item(input, &[PrepositionType::IN, PrepositionType::ON, PrepositionType::UNDER, PrepositionType::BEHIND]).map(CommandNode::PUT),
+	}
+}
+
+/*
+Determines whether an item is being turned on or off, with on or off
+either before or after the item
+*/
+fn turn(input: &mut Vec<Token>) -> Option<CommandNode> {
+	let on = match peek_token(input).map(|t| t.t_type.clone()) {
+		Some(TokenType::PREPOSITION(PrepositionType::ON)) => Some(true),
+		Some(TokenType::PREPOSITION(PrepositionType::OFF)) => Some(false),
+		_ => None,
+	};
+	if on.is_some() {
+		get_token(input);
+	}
+	let i = item(input)?;
+	let on = match on {
+		Some(b) => b,
+		None => match get_token(input).map(|t| t.t_type) {
+			Some(TokenType::PREPOSITION(PrepositionType::ON)) => true,
+			Some(TokenType::PREPOSITION(PrepositionType::OFF)) => false,
+			_ => return None,
 		},
+	};
+	if on {
+		Some(CommandNode::TURNON(i))
+	} else {
+		Some(CommandNode::TURNOFF(i))
+	}
+}
+
+/*
+Determines what is being locked or unlocked, and the key if one is
+given with "with"
+*/
+fn lock(input: &mut Vec<Token>) -> Option<(ItemNode, Option<ItemNode>)> {
+	let i = item(input)?;
+	if input.is_empty() {
+		return Some((i, None));
+	}
+	match preposition(input) {
+		Some(p_node) if p_node.prep == PrepositionType::WITH => Some((i, Some(p_node.item))),
 		_ => None,
 	}
 }
 
 /*
-Determines if a direction was specified
+Determines what, if anything, is being jumped into, onto or across
 */
-fn go(input: &mut Vec<&str>) -> Option<CommandNode> {
+fn jump(input: &mut Vec<Token>) -> Option<CommandNode> {
+	match peek_token(input).map(|t| t.t_type.clone()) {
+		None => Some(CommandNode::JUMP(None)),
+		Some(TokenType::PREPOSITION(_)) => preposition(input).map(|p_node| CommandNode::JUMP(Some(p_node.item))),
+		Some(_) => item(input).map(|i| CommandNode::JUMP(Some(i))),
+	}
+}
+
+/*
+Determines if a direction was specified. "in" is a preposition, but
+after go or climb it is the direction.
+*/
+fn go(input: &mut Vec<Token>) -> Option<CommandNode> {
 	match get_token(input) {
 		Some(next_token) => match next_token.t_type {
 			TokenType::DIRECTION(d) => Some(CommandNode::GO(d)),
+			TokenType::PREPOSITION(PrepositionType::IN) => Some(CommandNode::GO(DirectionType::IN)),
 			_ => None,
 		},
 		None => None,
@@ -127,13 +188,10 @@ fn go(input: &mut Vec<&str>) -> Option<CommandNode> {
 /*
 Determines if a preposition was specified
 */
-fn preposition(input: &mut Vec<&str>) -> Option<PrepositionNode> {
+fn preposition(input: &mut Vec<Token>) -> Option<PrepositionNode> {
 	match get_token(input) {
 		Some(next_token) => match next_token.t_type {
-			TokenType::PREPOSITION(p) => match item(input) {
-				Some(i) => Some(PrepositionNode::new(p, i)),
-				None => None,
-			},
+			TokenType::PREPOSITION(p) => item(input).map(|i| PrepositionNode::new(p, i)),
 			_ => None,
 		},
 		None => None,
@@ -141,26 +199,110 @@ fn preposition(input: &mut Vec<&str>) -> Option<PrepositionNode> {
 }
 
 /*
-Determines if an item was specified
+Determines if a subject item, one of the allowed prepositions and an
+object item were specified
 */
-#[allow(unused_variables)]
-fn item(input: &mut Vec<&str>) -> Option<ItemNode> {
-	let lexeme = peek_lexeme(input);
-	match get_token(input) {
-		Some(next_token) => match next_token.t_type {
-			TokenType::ITEM(i) => Some(ItemNode::new(i.as_str(), lexeme)),
-			TokenType::WORD => Some(ItemNode::new("UNKNOWN", lexeme)),
-			_ => None,
+fn item_to_item(input: &mut Vec<Token>, allowed: &[PrepositionType]) -> Option<ItemToItemNode> {
+	let subject = item(input)?;
+	match preposition(input) {
+		Some(p_node) => {
+			if allowed.contains(&p_node.prep) {
+				Some(ItemToItemNode::new(subject, p_node.prep, p_node.item))
+			} else {
+				None
+			}
 		},
 		None => None,
 	}
 }
 
+/*
+Determines if a list of items was specified, either as all (with an
+optional except clause) or as items separated by and/commas
+*/
+fn item_list(input: &mut Vec<Token>) -> Option<ItemListNode> {
+	let mut list = ItemListNode::new();
+	if is_list_token(input, ListType::ALL) {
+		get_token(input);
+		list.all = true;
+		if is_list_token(input, ListType::EXCEPT) {
+			get_token(input);
+			match items(input) {
+				Some(i) => list.except = i,
+				None => return None,
+			}
+		}
+	} else {
+		match items(input) {
+			Some(i) => list.items = i,
+			None => return None,
+		}
+	}
+	Some(list)
+}
+
+/*
+Determines if one or more items were specified, separated by and/commas
+*/
+fn items(input: &mut Vec<Token>) -> Option<Vec<ItemNode>> {
+	let mut list = Vec::new();
+	loop {
+		match item(input) {
+			Some(i) => list.push(i),
+			None => return None,
+		}
+		if !is_list_token(input, ListType::AND) {
+			break;
+		}
+		while is_list_token(input, ListType::AND) {
+			get_token(input);
+		}
+	}
+	Some(list)
+}
+
+/*
+Checks if the next Token is the given list word, leaving the input
+unchanged
+*/
+fn is_list_token(input: &[Token], l: ListType) -> bool {
+	match peek_token(input) {
+		Some(next_token) => next_token.t_type == TokenType::LIST(l),
+		None => false,
+	}
+}
+
+/*
+Determines if an item was specified, along with any adjectives before
+it. Articles are skipped.
+*/
+fn item(input: &mut Vec<Token>) -> Option<ItemNode> {
+	let mut adjectives: Vec<String> = Vec::new();
+	loop {
+		match get_token(input) {
+			Some(next_token) => match next_token.t_type {
+				TokenType::ADJECTIVE => adjectives.push(next_token.lexeme),
+				TokenType::ITEM(i) => {
+					let mut i_node = ItemNode::new(i.as_str(), next_token.lexeme.as_str());
+					i_node.adjectives = adjectives;
+					return Some(i_node);
+				},
+				TokenType::WORD => match next_token.lexeme.as_str() {
+					"the" | "a" | "an" => (),
+					_ => return Some(ItemNode::new("UNKNOWN", next_token.lexeme.as_str())),
+				},
+				_ => return None,
+			},
+			None => return None,
+		}
+	}
+}
+
 /*
 Other token types
 */
 #[allow(unused_variables)]
-fn other(o: OtherType, input: &mut Vec<&str>) -> Option<CommandNode> {
+fn other(o: OtherType, input: &mut [Token]) -> Option<CommandNode> {
 	Some(CommandNode::OTHER(o))
 	// match o {
 	// 	OtherType::YES => Some(CommandNode::OTHER(o)),
@@ -5,7 +5,8 @@ Used to convert single words into Tokens
 use std::fmt;
 use regex::Regex;
 
-use parsing::{TokenType,GameStateType,VerbType,PrepositionType,DirectionType,OtherType};
+use parsing::{TokenType,GameStateType,VerbType,PrepositionType,DirectionType,ListType,OtherType};
+use parsing::lexicon::Lexicon;
 
 /*
 Holds the token type and lexeme (the actual string typed by the
@@ -21,12 +22,11 @@ impl Token {
 	Creates a new Token from a lexeme.
 	*/
 	pub fn new(word: &str) -> Token {
-		let token_type: TokenType;
-		if is_alpha_string(word) {
-			token_type = TokenType::WORD;
+		let token_type = if is_alpha_string(word) {
+			TokenType::WORD
 		} else {
-			token_type = TokenType::BAD;
-		}
+			TokenType::BAD
+		};
 		let mut token = Token { t_type: token_type, lexeme: word.to_string() };
 		token.check_reserved();
 		token
@@ -40,27 +40,44 @@ impl Token {
 		match self.t_type {
 			TokenType::GAMESTATE(s) => match s {
 				GameStateType::QUIT => "QUIT".to_string(),
+				GameStateType::SAVE => "SAVE".to_string(),
+				GameStateType::RESTORE => "RESTORE".to_string(),
+				GameStateType::UNDO => "UNDO".to_string(),
+				GameStateType::RESTART => "RESTART".to_string(),
+				GameStateType::SCORE => "SCORE".to_string(),
+				GameStateType::SEED => "SEED".to_string(),
 			},
 			TokenType::VERB(v) => format!("VERB:{}", match v {
 				VerbType::LOOK => "LOOK".to_string(),
 				VerbType::INVENTORY => "INVENTORY".to_string(),
 				VerbType::GO => "GO".to_string(),
+				VerbType::JUMP => "JUMP".to_string(),
 				VerbType::TAKE => "TAKE".to_string(),
 				VerbType::DROP => "DROP".to_string(),
 				VerbType::EXAMINE => "EXAMINE".to_string(),
 				VerbType::USE => "USE".to_string(),
+				VerbType::OPEN => "OPEN".to_string(),
+				VerbType::CLOSE => "CLOSE".to_string(),
+				VerbType::LOCK => "LOCK".to_string(),
+				VerbType::UNLOCK => "UNLOCK".to_string(),
+				VerbType::TURN => "TURN".to_string(),
+				VerbType::LIGHT => "LIGHT".to_string(),
+				VerbType::EXTINGUISH => "EXTINGUISH".to_string(),
+				VerbType::CLIMB => "CLIMB".to_string(),
 				VerbType::CUT => "CUT".to_string(),
 				VerbType::HIT => "HIT".to_string(),
 				VerbType::TIE => "TIE".to_string(),
+				VerbType::PUT => "PUT".to_string(),
 			}),
 			TokenType::PREPOSITION(_) => "PREPOSITION".to_string(),
-			TokenType::DIRECTION(d) => format!("DIRECTION:{}", match d {
-				DirectionType::NORTH => "NORTH".to_string(),
-				DirectionType::SOUTH => "SOUTH".to_string(),
-				DirectionType::EAST => "EAST".to_string(),
-				DirectionType::WEST => "WEST".to_string(),
+			TokenType::DIRECTION(d) => format!("DIRECTION:{}", d.name().to_uppercase()),
+			TokenType::LIST(l) => format!("LIST:{}", match l {
+				ListType::ALL => "ALL".to_string(),
+				ListType::AND => "AND".to_string(),
+				ListType::EXCEPT => "EXCEPT".to_string(),
 			}),
 			TokenType::ITEM(_) => "ITEM".to_string(),
+			TokenType::ADJECTIVE => "ADJECTIVE".to_string(),
 			TokenType::OTHER(o) => match o {
 				OtherType::YES => "YES".to_string(),
 				OtherType::NO => "NO".to_string(),
@@ -70,6 +87,35 @@ impl Token {
 		}
 	}
 
+	/*
+	Creates a new Token from a lexeme, checking the world's item
+	vocabulary if it isn't a reserved word.
+	*/
+	pub fn with_lexicon(word: &str, lexicon: &Lexicon) -> Token {
+		let mut token = Token::new(word);
+		token.check_lexicon(lexicon);
+		token
+	}
+
+	/*
+	Checks to see if the lexeme is an item noun or adjective in the
+	lexicon. Reserved words are left alone.
+	*/
+	pub fn check_lexicon(&mut self, lexicon: &Lexicon) {
+		if self.t_type == TokenType::WORD {
+			self.t_type = match lexicon.noun(&self.lexeme) {
+				Some(i_type) => TokenType::ITEM(i_type.to_string()),
+				None => {
+					if lexicon.is_adjective(&self.lexeme) {
+						TokenType::ADJECTIVE
+					} else {
+						TokenType::WORD
+					}
+				},
+			};
+		}
+	}
+
 	/*
 	Checks to see if the lexeme matches reserved verbs, directions, etc.
 	*/
@@ -77,12 +123,22 @@ impl Token {
 		self.t_type = match self.lexeme.as_str() {
 			// GameState Tokens
 			"quit" => TokenType::GAMESTATE(GameStateType::QUIT),
+			"save" => TokenType::GAMESTATE(GameStateType::SAVE),
+			"restore" => TokenType::GAMESTATE(GameStateType::RESTORE),
+			"load" => TokenType::GAMESTATE(GameStateType::RESTORE),
+			"undo" => TokenType::GAMESTATE(GameStateType::UNDO),
+			"restart" => TokenType::GAMESTATE(GameStateType::RESTART),
+			"score" => TokenType::GAMESTATE(GameStateType::SCORE),
+			"seed" => TokenType::GAMESTATE(GameStateType::SEED),
 			// Verb Tokens
 			"look" => TokenType::VERB(VerbType::LOOK),
 			"l" => TokenType::VERB(VerbType::LOOK),
 			"inventory" => TokenType::VERB(VerbType::INVENTORY),
 			"i" => TokenType::VERB(VerbType::INVENTORY),
 			"go" => TokenType::VERB(VerbType::GO),
+			"climb" => TokenType::VERB(VerbType::CLIMB),
+			"jump" => TokenType::VERB(VerbType::JUMP),
+			"leap" => TokenType::VERB(VerbType::JUMP),
 			"get" => TokenType::VERB(VerbType::TAKE),
 			"take" => TokenType::VERB(VerbType::TAKE),
 			"drop" => TokenType::VERB(VerbType::DROP),
@@ -94,42 +150,63 @@ impl Token {
 			"smash" => TokenType::VERB(VerbType::HIT),
 			"cut" => TokenType::VERB(VerbType::CUT),
 			"tie" => TokenType::VERB(VerbType::TIE),
+			"turn" => TokenType::VERB(VerbType::TURN),
+			"switch" => TokenType::VERB(VerbType::TURN),
+			"light" => TokenType::VERB(VerbType::LIGHT),
+			"extinguish" => TokenType::VERB(VerbType::EXTINGUISH),
+			"douse" => TokenType::VERB(VerbType::EXTINGUISH),
+			"put" => TokenType::VERB(VerbType::PUT),
+			"place" => TokenType::VERB(VerbType::PUT),
+			"open" => TokenType::VERB(VerbType::OPEN),
+			"close" => TokenType::VERB(VerbType::CLOSE),
+			"shut" => TokenType::VERB(VerbType::CLOSE),
+			"lock" => TokenType::VERB(VerbType::LOCK),
+			"unlock" => TokenType::VERB(VerbType::UNLOCK),
 			// Preposition Tokens
 			"at" => TokenType::PREPOSITION(PrepositionType::AT),
 			"to" => TokenType::PREPOSITION(PrepositionType::TO),
 			"in" => TokenType::PREPOSITION(PrepositionType::IN),
 			"into" => TokenType::PREPOSITION(PrepositionType::IN),
 			"on" => TokenType::PREPOSITION(PrepositionType::ON),
+			"onto" => TokenType::PREPOSITION(PrepositionType::ON),
 			"under" => TokenType::PREPOSITION(PrepositionType::UNDER),
 			"behind" => TokenType::PREPOSITION(PrepositionType::BEHIND),
 			"with" => TokenType::PREPOSITION(PrepositionType::WITH),
+			"from" => TokenType::PREPOSITION(PrepositionType::FROM),
+			"over" => TokenType::PREPOSITION(PrepositionType::OVER),
+			"off" => TokenType::PREPOSITION(PrepositionType::OFF),
+			"across" => TokenType::PREPOSITION(PrepositionType::OVER),
 			// Direction Tokens
 			"north" => TokenType::DIRECTION(DirectionType::NORTH),
 			"south" => TokenType::DIRECTION(DirectionType::SOUTH),
 			"east" => TokenType::DIRECTION(DirectionType::EAST),
 			"west" => TokenType::DIRECTION(DirectionType::WEST),
+			"northeast" => TokenType::DIRECTION(DirectionType::NORTHEAST),
+			"northwest" => TokenType::DIRECTION(DirectionType::NORTHWEST),
+			"southeast" => TokenType::DIRECTION(DirectionType::SOUTHEAST),
+			"southwest" => TokenType::DIRECTION(DirectionType::SOUTHWEST),
+			"up" => TokenType::DIRECTION(DirectionType::UP),
+			"down" => TokenType::DIRECTION(DirectionType::DOWN),
+			"out" => TokenType::DIRECTION(DirectionType::OUT),
+			"outside" => TokenType::DIRECTION(DirectionType::OUT),
+			"inside" => TokenType::DIRECTION(DirectionType::IN),
 			"n" => TokenType::DIRECTION(DirectionType::NORTH),
 			"s" => TokenType::DIRECTION(DirectionType::SOUTH),
 			"e" => TokenType::DIRECTION(DirectionType::EAST),
 			"w" => TokenType::DIRECTION(DirectionType::WEST),
-			// Item Tokens
-			"key" => TokenType::ITEM("key".to_string()),
-			"pick" => TokenType::ITEM("pickaxe".to_string()),
-			"pickaxe" => TokenType::ITEM("pickaxe".to_string()),
-			"rope" => TokenType::ITEM("rope".to_string()),
-			"hammer" => TokenType::ITEM("hammer".to_string()),
-			"idol" => TokenType::ITEM("idol".to_string()),
-			"trees" => TokenType::ITEM("trees".to_string()),
-			"altar" => TokenType::ITEM("altar".to_string()),
-			"seal" => TokenType::ITEM("seal".to_string()),
-			"stones" => TokenType::ITEM("seal".to_string()),
-			"pit" => TokenType::ITEM("pit".to_string()),
-			"root" => TokenType::ITEM("root".to_string()),
-			"lantern" => TokenType::ITEM("lantern".to_string()),
-			"lamp" => TokenType::ITEM("lantern".to_string()),
-			"machete" => TokenType::ITEM("machete".to_string()),
-			"cobwebs" => TokenType::ITEM("cobwebs".to_string()),
-			"ceiling" => TokenType::ITEM("ceiling".to_string()),
+			"ne" => TokenType::DIRECTION(DirectionType::NORTHEAST),
+			"nw" => TokenType::DIRECTION(DirectionType::NORTHWEST),
+			"se" => TokenType::DIRECTION(DirectionType::SOUTHEAST),
+			"sw" => TokenType::DIRECTION(DirectionType::SOUTHWEST),
+			"u" => TokenType::DIRECTION(DirectionType::UP),
+			"d" => TokenType::DIRECTION(DirectionType::DOWN),
+			// List Tokens
+			"all" => TokenType::LIST(ListType::ALL),
+			"everything" => TokenType::LIST(ListType::ALL),
+			"and" => TokenType::LIST(ListType::AND),
+			"," => TokenType::LIST(ListType::AND),
+			"except" => TokenType::LIST(ListType::EXCEPT),
+			"but" => TokenType::LIST(ListType::EXCEPT),
 			// Other Tokens
 			"yes" => TokenType::OTHER(OtherType::YES),
 			"no" => TokenType::OTHER(OtherType::NO),
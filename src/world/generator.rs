@@ -0,0 +1,174 @@
+/*
+Generates temple worlds from a seed.
+
+The rooms are laid out on a grid, growing outwards from the first room
+one room at a time, so every room can be reached and every exit leads
+back the way it came. A route leads from the first room to the goal,
+where the player wins. Side branches lead off the route. Settings say
+how long the route is, how many branches there are, and how many
+puzzles.
+
+The passages are barred by barriers, like the temple's cobwebs (which
+need cutting) and stone seal (which needs smashing). The puzzles are
+planned by PuzzleGraph (see puzzle.rs), so they can always be solved,
+and the last barrier is always the one in front of the goal.
+
+What the player has come for, and the names and descriptions of the
+rooms, come from the world's Story (see story.rs).
+
+The same seed and settings always generate the same world.
+*/
+
+use game::rng::Rng;
+use obj::location::ExitBuilder;
+use parsing::DirectionType;
+use world::World;
+use world::puzzle::PuzzleGraph;
+use world::story::Story;
+
+pub const MAX_DEPTH: usize = 10;
+pub const MAX_BRANCHING: usize = 3;
+
+const PASSAGE_DESC: &str = "A passage leads";
+
+/*
+The directions rooms are laid out in, and the way each moves across
+the grid. The route only uses the first two.
+*/
+const GRID_DIRECTIONS: [(DirectionType, i32, i32); 4] = [
+	(DirectionType::NORTH, 0, 1),
+	(DirectionType::EAST, 1, 0),
+	(DirectionType::SOUTH, 0, -1),
+	(DirectionType::WEST, -1, 0),
+];
+
+/*
+A passage between two rooms, leading from one to the other in a
+direction. There is always an exit back the other way.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Passage {
+	pub from: usize,
+	pub direction: DirectionType,
+	pub to: usize,
+}
+
+/*
+How big a temple to generate:
+	depth: the number of rooms on the way from the first room to the
+	goal (1 to MAX_DEPTH)
+	branching: the number of side passages leading off that way, where
+	tools can be hidden (0 to MAX_BRANCHING)
+	puzzles: the number of barriers (1 to MAX_PUZZLES, and no more than
+	there are passages)
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+	pub depth: usize,
+	pub branching: usize,
+	pub puzzles: usize,
+}
+
+impl Default for Settings {
+	fn default() -> Settings {
+		Settings {
+			depth: 4,
+			branching: 2,
+			puzzles: 3,
+		}
+	}
+}
+
+/*
+Generates a temple from seed, with the default settings
+*/
+pub fn generate(seed: u64) -> World {
+	generate_with(seed, &Settings::default())
+}
+
+/*
+Generates a temple from seed
+*/
+pub fn generate_with(seed: u64, settings: &Settings) -> World {
+	generate_from(&mut Rng::new(seed), settings)
+}
+
+/*
+Generates a temple with rng, which can then be given to the game
+*/
+pub fn generate_from(rng: &mut Rng, settings: &Settings) -> World {
+	let story = Story::generate(rng);
+	let depth = settings.depth.clamp(1, MAX_DEPTH);
+	let branching = settings.branching.min(MAX_BRANCHING);
+	let passages = lay_out(rng, depth, branching);
+	let room_count = passages.len() + 1;
+	let goal = depth; // The route is laid out first
+
+	let mut locations = story.locations(rng, room_count, goal);
+
+	let mut exits: Vec<ExitBuilder> = passages.iter().map(|p| {
+		let mut exit = ExitBuilder::new();
+		exit.set_dest(p.to).set_desc(PASSAGE_DESC);
+		exit
+	}).collect();
+	let graph = PuzzleGraph::plan(rng, &passages, room_count, settings.puzzles, goal - 1);
+	graph.apply(rng, &passages, &mut locations, &mut exits);
+	for (p, exit) in passages.iter().zip(exits.iter()) {
+		locations[p.from].add_exit(p.direction, exit.finalize());
+		locations[p.to].add_exit(p.direction.opposite(), ExitBuilder::new()
+			.set_dest(p.from)
+			.set_desc(PASSAGE_DESC)
+			.finalize());
+	}
+
+	let ending = story.finish(rng, &mut locations, goal);
+	let mut world = World::new(locations);
+	world.endings.push(ending);
+	world.intro = format!("{}\n\n{}", story.title, story.intro);
+	world
+}
+
+/*
+Lays out the rooms on a grid. The route from the first room (room 0)
+to the goal comes first, only ever heading north or east so it never
+runs into itself, and the goal is room depth. Then each branch grows
+from a room on the route (not the goal), in any direction that is
+free, for one or two rooms. Each passage leads to a new room, so a
+passage always comes after the passage into the room it leaves from.
+*/
+fn lay_out(rng: &mut Rng, depth: usize, branching: usize) -> Vec<Passage> {
+	let mut cells: Vec<(i32, i32)> = vec![(0, 0)];
+	let mut passages = Vec::new();
+	for r in 0..depth {
+		let &(direction, dx, dy) = rng.choose(&GRID_DIRECTIONS[..2]);
+		cells.push((cells[r].0 + dx, cells[r].1 + dy));
+		passages.push(Passage {
+			from: r,
+			direction,
+			to: r + 1,
+		});
+	}
+	for _ in 0..branching {
+		let mut from = rng.below(depth);
+		for _ in 0..rng.between(1, 2) {
+			let mut directions = GRID_DIRECTIONS.to_vec();
+			rng.shuffle(&mut directions);
+			let free = directions.iter()
+				.map(|&(d, dx, dy)| (d, (cells[from].0 + dx, cells[from].1 + dy)))
+				.find(|&(_, cell)| !cells.contains(&cell));
+			match free {
+				Some((direction, cell)) => {
+					passages.push(Passage {
+						from,
+						direction,
+						to: cells.len(),
+					});
+					from = cells.len();
+					cells.push(cell);
+				},
+				None => break,
+			}
+		}
+	}
+	passages
+}
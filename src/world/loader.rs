@@ -0,0 +1,637 @@
+/*
+Loads worlds from text files, so new adventures don't need a recompile.
+
+A world file is a list of sections. Each section starts with a header in
+square brackets, followed by key = value lines. Blank lines and lines
+starting with # are ignored.
+
+	[location <id>]
+		name, desc, no_exit, dark (true if it needs a light source),
+		points (scored the first time the player enters)
+	Locations are numbered in the order they appear. The first one is
+	where the player starts.
+
+	[exit <location id> <direction>]
+		dest (a location id), desc (leave it empty to not list the exit),
+		travel_desc,
+		blocked_by (an item id), blocked_desc,
+		door (a door id, shared with the exit back the other way)
+
+	[door <id>]
+		type (defaults to the id), name, desc, aliases, adjectives,
+		open, locked, key (the item id that locks and unlocks it, needed if locked)
+
+	[item <id>]
+		Exactly one of:
+			location (a location id)
+			on, in, under, behind, attached_to (an item id)
+		type (defaults to the id), name, desc,
+		aliases, adjectives (lists separated by commas), is_on_desc,
+		light_damage_desc, heavy_damage_desc, destroyed_desc,
+		fixed, scenery, has_surface, has_inside, has_behind, has_under,
+		can_open, is_open, can_attach, can_turn_on, is_on,
+		gives_light (while on), fuel (turns it can stay on),
+		take_points (scored the first time it is taken),
+		destroy_points (scored when it is destroyed),
+		health, max_health, current_health,
+		damaged_by, damage_type (smashing or cutting)
+	Location and item descriptions can change as the game goes on, with
+	the tags described in src/obj/template.rs.
+
+	[ending <id>]
+		name (defaults to the id), text, kind (win, loss, death or neutral),
+		and at least one condition, all of which must hold:
+			holding (an item id), reached (a location id),
+			destroyed (an item id), turns (a number), flag (a name),
+			dark (true when the player can't see)
+	Endings are checked in the order they appear.
+
+	[hazard <id>]
+		name (defaults to the id), text, at most one trigger:
+			jump (an item id), enter (a location id)
+		and any of the ending conditions. Without a trigger, the hazard
+		is checked after every turn. Hazards kill the player.
+
+	[event <id>]
+		text (shown when it fires), exactly one of:
+			fuse (fires once, this many turns after it starts)
+			daemon (fires every this many turns until cancelled)
+		chance (the percent chance it fires each time it is due, from
+		1 to 100, defaulting to 100),
+		any of the ending conditions (it starts the first turn they all
+		hold, or with the game if there are none), and any effects:
+			sets_flag (a name), destroys (an item id),
+			location and desc (the location's description becomes desc),
+			starts, cancels (lists of event ids)
+
+Errors are returned as a message with the line number they were found on.
+*/
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+
+use uuid::Uuid;
+
+use game::ending::{Ending,EndingKind,Condition};
+use game::event::{Event,Effect,Timing};
+use game::hazard::{Hazard,Trigger};
+use obj::{DamageType,id_from_name};
+use obj::door::Door;
+use obj::item::{Item,ItemBuilder};
+use obj::location::{Location,ExitBuilder};
+use parsing::{TokenType,DirectionType,PrepositionType};
+use parsing::token::Token;
+use world::World;
+
+/*
+A [header] and the key = value lines under it
+*/
+struct Section {
+	kind: String,
+	args: Vec<String>,
+	line: usize,
+	props: Vec<(String, String, usize)>,
+}
+
+impl Section {
+	fn get(&self, key: &str) -> Option<&str> {
+		self.props.iter().find(|p| p.0 == key).map(|p| p.1.as_str())
+	}
+}
+
+/*
+Where an item starts the game
+*/
+#[allow(clippy::upper_case_acronyms)]
+enum Placement {
+	LOCATION(String),
+	INSIDE(PrepositionType, String),
+	ATTACHED(String),
+}
+
+/*
+Reads the world file at path and loads it
+*/
+pub fn load_world_file(path: &str) -> Result<World, String> {
+	let mut text = String::new();
+	match File::open(path) {
+		Ok(mut f) => match f.read_to_string(&mut text) {
+			Ok(_) => load_world(&text),
+			Err(e) => Err(format!("Could not read {}: {}", path, e)),
+		},
+		Err(e) => Err(format!("Could not open {}: {}", path, e)),
+	}
+}
+
+/*
+Loads a world from the text of a world file
+*/
+pub fn load_world(text: &str) -> Result<World, String> {
+	let sections = parse_sections(text)?;
+
+	// Locations first, so exits and items can refer to them
+	let mut locations: Vec<Location> = Vec::new();
+	let mut location_ids: HashMap<String, usize> = HashMap::new();
+	for section in sections.iter().filter(|s| s.kind == "location") {
+		let id = single_arg(section)?;
+		if location_ids.contains_key(id) {
+			return Err(format!("line {}: location '{}' is defined twice", section.line, id));
+		}
+		check_keys(section, &["name", "desc", "no_exit", "dark", "points"])?;
+		location_ids.insert(id.to_string(), locations.len());
+		let mut location = Location::new(
+			required(section, "name")?,
+			section.get("desc").unwrap_or(""),
+			section.get("no_exit").unwrap_or("You can't go that direction."));
+		if let Some(d) = section.get("dark") {
+			location.is_dark = boolean(d, section.line)?;
+		}
+		if let Some(p) = section.get("points") {
+			location.points = number(p, section.line)?;
+		}
+		locations.push(location);
+	}
+	if locations.is_empty() {
+		return Err("the world has no locations".to_string());
+	}
+
+	// Then items, finalized so exits can be blocked by their ids
+	let mut items: HashMap<String, Item> = HashMap::new();
+	let mut item_ids: HashMap<String, Uuid> = HashMap::new();
+	let mut placements: Vec<(String, Placement, usize)> = Vec::new();
+	for section in sections.iter().filter(|s| s.kind == "item") {
+		let id = single_arg(section)?;
+		if items.contains_key(id) {
+			return Err(format!("line {}: item '{}' is defined twice", section.line, id));
+		}
+		let item = build_item(section, id)?;
+		item_ids.insert(id.to_string(), item.get_id());
+		items.insert(id.to_string(), item);
+		placements.push((id.to_string(), placement(section, id)?, section.line));
+	}
+
+	let mut endings: Vec<Ending> = Vec::new();
+	for section in sections.iter().filter(|s| s.kind == "ending") {
+		endings.push(build_ending(section, &location_ids, &item_ids)?);
+	}
+	let mut hazards: Vec<Hazard> = Vec::new();
+	for section in sections.iter().filter(|s| s.kind == "hazard") {
+		hazards.push(build_hazard(section, &location_ids, &item_ids)?);
+	}
+	let mut events: Vec<Event> = Vec::new();
+	for section in sections.iter().filter(|s| s.kind == "event") {
+		let event = build_event(section, &location_ids, &item_ids)?;
+		if events.iter().any(|e| e.id == event.id) {
+			return Err(format!("line {}: event '{}' is defined twice", section.line, event.id));
+		}
+		events.push(event);
+	}
+	for section in sections.iter().filter(|s| s.kind == "event") {
+		for &(ref key, ref value, line) in &section.props {
+			if key == "starts" || key == "cancels" {
+				if let Some(id) = list(value).into_iter().find(|id| !events.iter().any(|e| e.id == *id)) {
+					return Err(format!("line {}: unknown event '{}'", line, id));
+				}
+			}
+		}
+	}
+
+	let mut doors: HashMap<String, Door> = HashMap::new();
+	for section in sections.iter().filter(|s| s.kind == "door") {
+		let id = single_arg(section)?;
+		if doors.contains_key(id) {
+			return Err(format!("line {}: door '{}' is defined twice", section.line, id));
+		}
+		doors.insert(id.to_string(), build_door(section, id, &item_ids)?);
+	}
+
+	for section in sections.iter().filter(|s| s.kind == "exit") {
+		if section.args.len() != 2 {
+			return Err(format!("line {}: exits need a location and a direction, like [exit hall north]", section.line));
+		}
+		check_keys(section, &["dest", "desc", "travel_desc", "blocked_by", "blocked_desc", "door"])?;
+		let from = find_location(&location_ids, &section.args[0], section.line)?;
+		let d = direction(&section.args[1], section.line)?;
+		let dest = find_location(&location_ids, required(section, "dest")?, section.line)?;
+		let mut builder = ExitBuilder::new();
+		builder.set_dest(dest);
+		if let Some(s) = section.get("desc") {
+			builder.set_desc(s);
+		}
+		if let Some(s) = section.get("travel_desc") {
+			builder.set_travel_desc(s);
+		}
+		if let Some(s) = section.get("blocked_by") {
+			match item_ids.get(s) {
+				Some(id) => builder.set_blocked_by_item(*id),
+				None => return Err(format!("line {}: unknown blocking item '{}'", section.line, s)),
+			};
+		}
+		if let Some(s) = section.get("blocked_desc") {
+			builder.set_blocked_desc(s);
+		}
+		if let Some(s) = section.get("door") {
+			match doors.get(s) {
+				Some(door) => builder.set_door(door),
+				None => return Err(format!("line {}: unknown door '{}'", section.line, s)),
+			};
+		}
+		locations[from].add_exit(d, builder.finalize());
+	}
+
+	// Finally, nest items inside each other and put them in locations
+	for &(ref id, ref place, line) in &placements {
+		if let Placement::LOCATION(ref l) = *place {
+			let loc = find_location(&location_ids, l, line)?;
+			let item = assemble_item(id, &placements, &mut items)?;
+			locations[loc].add_item(item);
+		}
+	}
+	if let Some(&(ref id, _, line)) = placements.iter().find(|p| items.contains_key(&p.0)) {
+		return Err(format!("line {}: item '{}' never ends up in a location (is it inside itself, or inside a missing item?)", line, id));
+	}
+	Ok(World {
+		locations,
+		endings,
+		hazards,
+		events,
+		intro: String::new(),
+	})
+}
+
+/*
+Splits the text into sections
+*/
+fn parse_sections(text: &str) -> Result<Vec<Section>, String> {
+	let mut sections: Vec<Section> = Vec::new();
+	for (n, raw) in text.lines().enumerate() {
+		let line_no = n + 1;
+		let line = raw.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		if line.starts_with('[') {
+			if !line.ends_with(']') {
+				return Err(format!("line {}: section header is missing ']'", line_no));
+			}
+			let mut words = line[1..line.len() - 1].split_whitespace().map(|w| w.to_string());
+			let kind = match words.next() {
+				Some(k) => k,
+				None => return Err(format!("line {}: empty section header", line_no)),
+			};
+			if kind != "location" && kind != "exit" && kind != "item" && kind != "ending" && kind != "hazard" && kind != "event" && kind != "door" {
+				return Err(format!("line {}: unknown section '{}'", line_no, kind));
+			}
+			sections.push(Section {
+				kind,
+				args: words.collect(),
+				line: line_no,
+				props: Vec::new(),
+			});
+		} else {
+			let (key, value) = match line.find('=') {
+				Some(i) => (line[..i].trim(), line[i + 1..].trim()),
+				None => return Err(format!("line {}: expected key = value", line_no)),
+			};
+			match sections.last_mut() {
+				Some(section) => {
+					if section.get(key).is_some() {
+						return Err(format!("line {}: '{}' is set twice", line_no, key));
+					}
+					section.props.push((key.to_string(), value.to_string(), line_no));
+				},
+				None => return Err(format!("line {}: '{}' is set before any section", line_no, key)),
+			}
+		}
+	}
+	Ok(sections)
+}
+
+fn single_arg(section: &Section) -> Result<&str, String> {
+	if section.args.len() == 1 {
+		Ok(&section.args[0])
+	} else {
+		Err(format!("line {}: {} sections need exactly one id, like [{} name]", section.line, section.kind, section.kind))
+	}
+}
+
+fn required<'a>(section: &'a Section, key: &str) -> Result<&'a str, String> {
+	match section.get(key) {
+		Some(s) => Ok(s),
+		None => Err(format!("line {}: {} is missing '{}'", section.line, section.kind, key)),
+	}
+}
+
+fn check_keys(section: &Section, allowed: &[&str]) -> Result<(), String> {
+	match section.props.iter().find(|p| !allowed.contains(&p.0.as_str())) {
+		Some(p) => Err(format!("line {}: unknown {} key '{}'", p.2, section.kind, p.0)),
+		None => Ok(()),
+	}
+}
+
+fn find_location(location_ids: &HashMap<String, usize>, id: &str, line: usize) -> Result<usize, String> {
+	match location_ids.get(id) {
+		Some(l) => Ok(*l),
+		None => Err(format!("line {}: unknown location '{}'", line, id)),
+	}
+}
+
+fn direction(word: &str, line: usize) -> Result<DirectionType, String> {
+	match DirectionType::from_name(word) {
+		Some(d) => Ok(d),
+		None => match Token::new(word).t_type {
+			TokenType::DIRECTION(d) => Ok(d),
+			_ => Err(format!("line {}: unknown direction '{}'", line, word)),
+		},
+	}
+}
+
+fn list(value: &str) -> Vec<&str> {
+	value.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).collect()
+}
+
+fn boolean(value: &str, line: usize) -> Result<bool, String> {
+	match value {
+		"true" | "yes" => Ok(true),
+		"false" | "no" => Ok(false),
+		_ => Err(format!("line {}: expected true or false, found '{}'", line, value)),
+	}
+}
+
+fn number(value: &str, line: usize) -> Result<i32, String> {
+	match value.parse::<i32>() {
+		Ok(n) => Ok(n),
+		Err(_) => Err(format!("line {}: expected a number, found '{}'", line, value)),
+	}
+}
+
+fn turns(value: &str, line: usize) -> Result<u32, String> {
+	match value.parse::<u32>() {
+		Ok(n) => Ok(n),
+		Err(_) => Err(format!("line {}: expected a number of turns, found '{}'", line, value)),
+	}
+}
+
+fn percent(value: &str, line: usize) -> Result<u32, String> {
+	match value.parse::<u32>() {
+		Ok(n) if (1..=100).contains(&n) => Ok(n),
+		_ => Err(format!("line {}: expected a percent from 1 to 100, found '{}'", line, value)),
+	}
+}
+
+fn damage_type(value: &str, line: usize) -> Result<DamageType, String> {
+	match value {
+		"smashing" => Ok(DamageType::SMASHING),
+		"cutting" => Ok(DamageType::CUTTING),
+		_ => Err(format!("line {}: expected smashing or cutting, found '{}'", line, value)),
+	}
+}
+
+/*
+Runs an [item] section through an ItemBuilder
+*/
+fn build_item(section: &Section, id: &str) -> Result<Item, String> {
+	let i_type = section.get("type").unwrap_or(id);
+	if i_type == "UNKNOWN" {
+		return Err(format!("line {}: items can't have the type UNKNOWN", section.line));
+	}
+	let mut builder = ItemBuilder::new(i_type, required(section, "name")?, section.get("desc").unwrap_or(""));
+	builder.set_id(id_from_name(&format!("item {}", id)));
+	for &(ref key, ref value, line) in &section.props {
+		match key.as_str() {
+			"type" | "name" | "desc" => (),
+			"location" | "on" | "in" | "under" | "behind" | "attached_to" => (),
+			"aliases" => for alias in list(value) {
+				builder.add_alias(alias);
+			},
+			"adjectives" => for adjective in list(value) {
+				builder.add_adjective(adjective);
+			},
+			"is_on_desc" => { builder.set_is_on_desc(value); },
+			"light_damage_desc" => { builder.set_light_damage_desc(value); },
+			"heavy_damage_desc" => { builder.set_heavy_damage_desc(value); },
+			"destroyed_desc" => { builder.set_destroyed_desc(value); },
+			"fixed" => { builder.set_fixed(boolean(value, line)?); },
+			"scenery" => { builder.set_scenery(boolean(value, line)?); },
+			"has_surface" => { builder.set_has_surface(boolean(value, line)?); },
+			"has_inside" => { builder.set_has_inside(boolean(value, line)?); },
+			"has_behind" => { builder.set_has_behind(boolean(value, line)?); },
+			"has_under" => { builder.set_has_under(boolean(value, line)?); },
+			"can_open" => { builder.set_can_open(boolean(value, line)?); },
+			"is_open" => { builder.set_is_open(boolean(value, line)?); },
+			"can_attach" => { builder.set_can_attach(boolean(value, line)?); },
+			"can_turn_on" => { builder.set_can_turn_on(boolean(value, line)?); },
+			"is_on" => { builder.set_is_on(boolean(value, line)?); },
+			"gives_light" => { builder.set_gives_light(boolean(value, line)?); },
+			"fuel" => { builder.set_fuel(number(value, line)?); },
+			"take_points" => { builder.set_take_points(number(value, line)?); },
+			"destroy_points" => { builder.set_destroy_points(number(value, line)?); },
+			"health" => { builder.set_health(number(value, line)?); },
+			"max_health" => { builder.set_max_health(number(value, line)?); },
+			"current_health" => { builder.set_current_health(number(value, line)?); },
+			"damaged_by" => { builder.set_damaged_by(damage_type(value, line)?); },
+			"damage_type" => { builder.set_damage_type(damage_type(value, line)?); },
+			_ => return Err(format!("line {}: unknown item key '{}'", line, key)),
+		}
+	}
+	Ok(builder.finalize())
+}
+
+/*
+Builds a Door from a [door] section
+*/
+fn build_door(section: &Section, id: &str, item_ids: &HashMap<String, Uuid>) -> Result<Door, String> {
+	let i_type = section.get("type").unwrap_or(id);
+	if i_type == "UNKNOWN" {
+		return Err(format!("line {}: doors can't have the type UNKNOWN", section.line));
+	}
+	let mut door = Door::new(i_type, required(section, "name")?, section.get("desc").unwrap_or(""));
+	door.set_id(id_from_name(&format!("door {}", id)));
+	for &(ref key, ref value, line) in &section.props {
+		match key.as_str() {
+			"type" | "name" | "desc" => (),
+			"aliases" => door.aliases = list(value).iter().map(|s| s.to_string()).collect(),
+			"adjectives" => door.adjectives = list(value).iter().map(|s| s.to_string()).collect(),
+			"open" => door.is_open = boolean(value, line)?,
+			"locked" => door.is_locked = boolean(value, line)?,
+			"key" => door.key = Some(find_item(item_ids, value, line)?),
+			_ => return Err(format!("line {}: unknown door key '{}'", line, key)),
+		}
+	}
+	if door.is_open && door.is_locked {
+		return Err(format!("line {}: door '{}' can't be both open and locked", section.line, id));
+	}
+	if door.is_locked && door.key.is_none() {
+		return Err(format!("line {}: door '{}' is locked and has no key", section.line, id));
+	}
+	Ok(door)
+}
+
+/*
+Builds an Ending from an [ending] section
+*/
+fn build_ending(section: &Section, location_ids: &HashMap<String, usize>, item_ids: &HashMap<String, Uuid>) -> Result<Ending, String> {
+	let id = single_arg(section)?;
+	let kind = match required(section, "kind")? {
+		"win" => EndingKind::WIN,
+		"loss" => EndingKind::LOSS,
+		"death" => EndingKind::DEATH,
+		"neutral" => EndingKind::NEUTRAL,
+		k => return Err(format!("line {}: expected win, loss, death or neutral, found '{}'", section.line, k)),
+	};
+	let mut ending = Ending::new(section.get("name").unwrap_or(id), section.get("text").unwrap_or(""), kind);
+	for &(ref key, ref value, line) in &section.props {
+		match key.as_str() {
+			"name" | "text" | "kind" => (),
+			_ => ending.add_condition(condition(section, key, value, line, location_ids, item_ids)?),
+		}
+	}
+	if ending.conditions.is_empty() {
+		return Err(format!("line {}: ending '{}' needs at least one condition", section.line, id));
+	}
+	Ok(ending)
+}
+
+/*
+Builds a Hazard from a [hazard] section
+*/
+fn build_hazard(section: &Section, location_ids: &HashMap<String, usize>, item_ids: &HashMap<String, Uuid>) -> Result<Hazard, String> {
+	let id = single_arg(section)?;
+	let mut ending = Ending::new(section.get("name").unwrap_or(id), section.get("text").unwrap_or(""), EndingKind::DEATH);
+	let mut trigger = Trigger::TURN;
+	for &(ref key, ref value, line) in &section.props {
+		let t = match key.as_str() {
+			"name" | "text" => continue,
+			"jump" => Trigger::JUMP(find_item(item_ids, value, line)?),
+			"enter" => Trigger::ENTER(find_location(location_ids, value, line)?),
+			_ => {
+				ending.add_condition(condition(section, key, value, line, location_ids, item_ids)?);
+				continue;
+			},
+		};
+		if trigger != Trigger::TURN {
+			return Err(format!("line {}: hazard '{}' can only have one of jump or enter", line, id));
+		}
+		trigger = t;
+	}
+	Ok(Hazard::new(trigger, ending))
+}
+
+/*
+Builds an Event from an [event] section
+*/
+fn build_event(section: &Section, location_ids: &HashMap<String, usize>, item_ids: &HashMap<String, Uuid>) -> Result<Event, String> {
+	let id = single_arg(section)?;
+	let timing = match (section.get("fuse"), section.get("daemon")) {
+		(Some(n), None) => Timing::FUSE(turns(n, section.line)?),
+		(None, Some(n)) => match turns(n, section.line)? {
+			0 => return Err(format!("line {}: a daemon must wait at least one turn", section.line)),
+			n => Timing::DAEMON(n),
+		},
+		_ => return Err(format!("line {}: event '{}' needs exactly one of fuse or daemon", section.line, id)),
+	};
+	let mut event = Event::new(id, section.get("text").unwrap_or(""), timing);
+	if let Some(c) = section.get("chance") {
+		event.chance = percent(c, section.line)?;
+	}
+	match (section.get("location"), section.get("desc")) {
+		(Some(l), Some(d)) => event.add_effect(Effect::DESCRIBE(find_location(location_ids, l, section.line)?, d.to_string())),
+		(None, None) => (),
+		_ => return Err(format!("line {}: event '{}' needs both location and desc to change a description", section.line, id)),
+	}
+	for &(ref key, ref value, line) in &section.props {
+		match key.as_str() {
+			"text" | "fuse" | "daemon" | "chance" | "location" | "desc" => (),
+			"sets_flag" => event.add_effect(Effect::FLAG(value.to_string())),
+			"destroys" => event.add_effect(Effect::DESTROY(find_item(item_ids, value, line)?)),
+			"starts" => for e in list(value) {
+				event.add_effect(Effect::START(e.to_string()));
+			},
+			"cancels" => for e in list(value) {
+				event.add_effect(Effect::CANCEL(e.to_string()));
+			},
+			_ => event.add_condition(condition(section, key, value, line, location_ids, item_ids)?),
+		}
+	}
+	Ok(event)
+}
+
+/*
+Reads one of the conditions shared by endings, hazards and events
+*/
+fn condition(section: &Section, key: &str, value: &str, line: usize, location_ids: &HashMap<String, usize>, item_ids: &HashMap<String, Uuid>) -> Result<Condition, String> {
+	match key {
+		"holding" => Ok(Condition::HOLDING(find_item(item_ids, value, line)?)),
+		"reached" => Ok(Condition::REACHED(find_location(location_ids, value, line)?)),
+		"destroyed" => Ok(Condition::DESTROYED(find_item(item_ids, value, line)?)),
+		"turns" => Ok(Condition::TURNS(turns(value, line)?)),
+		"flag" => Ok(Condition::FLAG(value.to_string())),
+		"dark" => match boolean(value, line)? {
+			true => Ok(Condition::DARK),
+			false => Err(format!("line {}: dark can only be true", line)),
+		},
+		_ => Err(format!("line {}: unknown {} key '{}'", line, section.kind, key)),
+	}
+}
+
+fn find_item(item_ids: &HashMap<String, Uuid>, id: &str, line: usize) -> Result<Uuid, String> {
+	match item_ids.get(id) {
+		Some(i) => Ok(*i),
+		None => Err(format!("line {}: unknown item '{}'", line, id)),
+	}
+}
+
+fn placement(section: &Section, id: &str) -> Result<Placement, String> {
+	let mut found: Vec<Placement> = Vec::new();
+	if let Some(l) = section.get("location") {
+		found.push(Placement::LOCATION(l.to_string()));
+	}
+	if let Some(i) = section.get("on") {
+		found.push(Placement::INSIDE(PrepositionType::ON, i.to_string()));
+	}
+	if let Some(i) = section.get("in") {
+		found.push(Placement::INSIDE(PrepositionType::IN, i.to_string()));
+	}
+	if let Some(i) = section.get("under") {
+		found.push(Placement::INSIDE(PrepositionType::UNDER, i.to_string()));
+	}
+	if let Some(i) = section.get("behind") {
+		found.push(Placement::INSIDE(PrepositionType::BEHIND, i.to_string()));
+	}
+	if let Some(i) = section.get("attached_to") {
+		found.push(Placement::ATTACHED(i.to_string()));
+	}
+	match found.len() {
+		1 => Ok(found.remove(0)),
+		0 => Err(format!("line {}: item '{}' needs one of location, on, in, under, behind or attached_to", section.line, id)),
+		_ => Err(format!("line {}: item '{}' can only be in one place", section.line, id)),
+	}
+}
+
+/*
+Takes the item out of the map, along with every item placed on, in,
+under, behind or attached to it
+*/
+fn assemble_item(id: &str, placements: &[(String, Placement, usize)], items: &mut HashMap<String, Item>) -> Result<Item, String> {
+	let mut item = match items.remove(id) {
+		Some(i) => i,
+		None => return Err(format!("item '{}' is inside itself", id)),
+	};
+	for &(ref child_id, ref place, line) in placements {
+		match *place {
+			Placement::INSIDE(p, ref parent) if parent == id => {
+				let child = assemble_item(child_id, placements, items)?;
+				if item.place_item(p, child).is_some() {
+					return Err(format!("line {}: item '{}' can't hold anything there (check its has_ attributes)", line, id));
+				}
+			},
+			Placement::ATTACHED(ref parent) if parent == id => {
+				let child = assemble_item(child_id, placements, items)?;
+				item.attach_item(child);
+			},
+			_ => (),
+		}
+	}
+	Ok(item)
+}
@@ -1 +1,51 @@
-pub mod builder;
\ No newline at end of file
+pub mod builder;
+pub mod generator;
+pub mod loader;
+pub mod puzzle;
+pub mod story;
+pub mod validator;
+
+use game::ending::Ending;
+use game::event::Event;
+use game::hazard::Hazard;
+use obj::location::Location;
+use world::validator::Problem;
+
+/*
+Everything a world file defines: the locations, with their exits and
+items, the ways the game can end, the ways the player can die, and the
+events that happen on their own. The intro, if there is one, is shown
+before the game begins.
+*/
+#[derive(Debug, Clone)]
+pub struct World {
+	pub locations: Vec<Location>,
+	pub endings: Vec<Ending>,
+	pub hazards: Vec<Hazard>,
+	pub events: Vec<Event>,
+	pub intro: String,
+}
+
+impl World {
+	/*
+	Creates a World from its locations, with no endings, hazards,
+	events or intro.
+	*/
+	pub fn new(locations: Vec<Location>) -> World {
+		World {
+			locations,
+			endings: Vec::new(),
+			hazards: Vec::new(),
+			events: Vec::new(),
+			intro: String::new(),
+		}
+	}
+
+	/*
+	Checks the world for broken exits, unreachable locations and items
+	the player can't name. The player starts in the first location.
+	*/
+	pub fn validate(&self) -> Vec<Problem> {
+		validator::validate(&self.locations, 0)
+	}
+}
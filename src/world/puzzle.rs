@@ -0,0 +1,326 @@
+/*
+Lock-and-key puzzles for generated worlds.
+
+A puzzle is a barrier across a passage (the lock) and the thing that
+clears it (the key). A barrier either has to be destroyed with a tool
+of the right damage type, like the temple's cobwebs and seal, or needs
+something tied to it before it can be crossed, like the root over the
+temple's pit.
+
+The puzzles form a graph: a key can be behind another barrier, so that
+barrier has to be cleared first. The graph is planned by opening the
+world up one barrier at a time. Each key goes somewhere the player can
+already reach, so the puzzles can always be solved in the order they
+were planned, and the last one is always the one in front of the goal.
+A tool that has already been placed is reused by any later barrier it
+can clear. Something to tie is used up, so there is at most one of
+those puzzles, and no way to waste its key.
+*/
+
+use game::rng::Rng;
+use obj::DamageType;
+use obj::item::{Item,ItemBuilder};
+use obj::location::{ExitBuilder,Location};
+use world::generator::Passage;
+
+/*
+What it takes to get past a barrier
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Need {
+	DAMAGE(DamageType), // Destroying it with a tool of this damage type
+	ATTACHMENT, // Tying something to it
+}
+
+/*
+One barrier and its key:
+	passage: the index of the passage it blocks
+	need: what clears it
+	key_room: the room its key is in, or None if an earlier puzzle's key
+	also clears it
+	after: the puzzles that have to be solved to reach the barrier and
+	its key
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+	pub passage: usize,
+	pub need: Need,
+	pub key_room: Option<usize>,
+	pub after: Vec<usize>,
+}
+
+/*
+The puzzles of a world, in an order they can be solved in
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct PuzzleGraph {
+	pub puzzles: Vec<Puzzle>,
+}
+
+/*
+A kind of barrier:
+	need: what clears it
+	i_type, name, desc: the barrier item
+	blocked_desc: shown when the player tries to get past it
+	destroyed_desc: the barrier's description once it has been
+	destroyed, for barriers that need damage
+*/
+struct BarrierKind {
+	need: Need,
+	i_type: &'static str,
+	name: &'static str,
+	desc: &'static str,
+	blocked_desc: &'static str,
+	destroyed_desc: &'static str,
+}
+
+const BARRIERS: [BarrierKind; 9] = [
+	BarrierKind {
+		need: Need::DAMAGE(DamageType::CUTTING),
+		i_type: "cobwebs",
+		name: "thick cobwebs",
+		desc: "Cobwebs fill the passage, making it difficult to move any further.",
+		blocked_desc: "The cobwebs are too thick to push through.",
+		destroyed_desc: "The cobwebs hang limp against the wall, slashed through.",
+	},
+	BarrierKind {
+		need: Need::DAMAGE(DamageType::CUTTING),
+		i_type: "vines",
+		name: "tangled vines",
+		desc: "Vines have grown through a crack in the ceiling and choke the passage.",
+		blocked_desc: "The vines are too tangled to squeeze through.",
+		destroyed_desc: "Severed vines lie in a heap on the floor.",
+	},
+	BarrierKind {
+		need: Need::DAMAGE(DamageType::CUTTING),
+		i_type: "curtain",
+		name: "a heavy curtain",
+		desc: "A heavy curtain of woven rope hangs across the doorway, nailed fast at every edge.",
+		blocked_desc: "The curtain is nailed down too firmly to get past.",
+		destroyed_desc: "The curtain hangs in tatters, cut open down the middle.",
+	},
+	BarrierKind {
+		need: Need::DAMAGE(DamageType::SMASHING),
+		i_type: "seal",
+		name: "a stone seal",
+		desc: "The doorway is blocked with a seal of stone bricks. They appear to be worn with age.",
+		blocked_desc: "The door is sealed.",
+		destroyed_desc: "The open door lies before you, the stone seal in pieces.",
+	},
+	BarrierKind {
+		need: Need::DAMAGE(DamageType::SMASHING),
+		i_type: "rubble",
+		name: "a heap of rubble",
+		desc: "Part of the ceiling has fallen in, and rubble blocks the passage.",
+		blocked_desc: "The rubble is piled too high to climb over.",
+		destroyed_desc: "Broken rubble has been cleared to either side of the passage.",
+	},
+	BarrierKind {
+		need: Need::DAMAGE(DamageType::SMASHING),
+		i_type: "statue",
+		name: "a toppled statue",
+		desc: "A huge statue has toppled across the doorway.",
+		blocked_desc: "The statue is wedged across the doorway.",
+		destroyed_desc: "The statue lies in pieces, and the doorway is clear.",
+	},
+	BarrierKind {
+		need: Need::ATTACHMENT,
+		i_type: "beam",
+		name: "a wooden beam",
+		desc: "A sturdy beam spans the ceiling above a wide chasm.",
+		blocked_desc: "The chasm is too wide to jump across.",
+		destroyed_desc: "",
+	},
+	BarrierKind {
+		need: Need::ATTACHMENT,
+		i_type: "hook",
+		name: "an iron hook",
+		desc: "An iron hook is set into the lip of a deep shaft.",
+		blocked_desc: "The shaft is too deep to climb down.",
+		destroyed_desc: "",
+	},
+	BarrierKind {
+		need: Need::ATTACHMENT,
+		i_type: "ring",
+		name: "a bronze ring",
+		desc: "A bronze ring is fixed to the wall beside a sheer drop.",
+		blocked_desc: "The drop is too sheer to climb.",
+		destroyed_desc: "",
+	},
+];
+
+/*
+Keys: what they clear, type, name, description
+*/
+const KEYS: [(Need, &str, &str, &str); 9] = [
+	(Need::DAMAGE(DamageType::CUTTING), "machete", "a machete", "The machete is sharp. It's perfect for hacking through vegetation."),
+	(Need::DAMAGE(DamageType::CUTTING), "knife", "a hunting knife", "The knife's blade is long and keen."),
+	(Need::DAMAGE(DamageType::CUTTING), "sickle", "a bronze sickle", "A curved bronze blade on a wooden handle."),
+	(Need::DAMAGE(DamageType::SMASHING), "pickaxe", "a pickaxe", "It's a large, unwieldy pickaxe, with a head built for busting through rock."),
+	(Need::DAMAGE(DamageType::SMASHING), "hammer", "a heavy hammer", "A stonemason's hammer, its head scarred from use."),
+	(Need::DAMAGE(DamageType::SMASHING), "mace", "a stone mace", "A ceremonial mace with a head of polished granite."),
+	(Need::ATTACHMENT, "rope", "a rope", "The rope is about twenty feet long, and is of fine quality."),
+	(Need::ATTACHMENT, "chain", "a length of chain", "The chain is light, but every link is sound."),
+	(Need::ATTACHMENT, "cord", "a braided cord", "A long cord, braided from strips of hide."),
+];
+
+pub const MAX_PUZZLES: usize = 7; // Three of each damage type, and one thing to tie
+
+impl PuzzleGraph {
+	/*
+	Plans count puzzles (at most MAX_PUZZLES, and at most one per
+	passage) on the passages of a world with room_count rooms. The
+	passage last leads to the goal, and its puzzle is always solved
+	last.
+	*/
+	pub fn plan(rng: &mut Rng, passages: &[Passage], room_count: usize, count: usize, last: usize) -> PuzzleGraph {
+		let count = count.clamp(1, MAX_PUZZLES).min(passages.len());
+		let mut barred: Vec<usize> = (0..passages.len()).filter(|&p| p != last).collect();
+		rng.shuffle(&mut barred);
+		barred.truncate(count - 1);
+		barred.push(last);
+
+		let mut needs: Vec<Need> = vec![
+			Need::DAMAGE(DamageType::CUTTING), Need::DAMAGE(DamageType::CUTTING), Need::DAMAGE(DamageType::CUTTING),
+			Need::DAMAGE(DamageType::SMASHING), Need::DAMAGE(DamageType::SMASHING), Need::DAMAGE(DamageType::SMASHING),
+			Need::ATTACHMENT,
+		];
+		rng.shuffle(&mut needs);
+
+		let mut puzzles: Vec<Puzzle> = Vec::new();
+		let mut closed = barred;
+		while !closed.is_empty() {
+			let reachable = reachable_rooms(passages, &closed, room_count);
+			let ready: Vec<usize> = closed.iter().cloned()
+				.filter(|&p| reachable[passages[p].from] && (p != last || closed.len() == 1))
+				.collect();
+			let passage = *rng.choose(&ready);
+			let need = needs.pop().unwrap();
+			let key_room = match puzzles.iter().position(|z| z.need == need && z.key_room.is_some()) {
+				Some(_) if need != Need::ATTACHMENT => None,
+				_ => {
+					let rooms: Vec<usize> = (0..room_count).filter(|&r| reachable[r]).collect();
+					Some(*rng.choose(&rooms))
+				},
+			};
+			let key_at = match key_room {
+				Some(r) => r,
+				None => puzzles.iter().filter(|z| z.need == need).find_map(|z| z.key_room).unwrap(),
+			};
+			let mut after: Vec<usize> = route_to(passages, key_at).into_iter()
+				.chain(route_to(passages, passages[passage].from))
+				.filter_map(|p| puzzles.iter().position(|z| z.passage == p))
+				.collect();
+			if key_room.is_none() {
+				after.extend(puzzles.iter().position(|z| z.need == need));
+			}
+			after.sort();
+			after.dedup();
+			puzzles.push(Puzzle {
+				passage,
+				need,
+				key_room,
+				after,
+			});
+			closed.retain(|&p| p != passage);
+		}
+		PuzzleGraph {
+			puzzles,
+		}
+	}
+
+	/*
+	Returns true if every puzzle comes after the puzzles it depends on
+	*/
+	pub fn is_ordered(&self) -> bool {
+		self.puzzles.iter().enumerate().all(|(n, z)| z.after.iter().all(|&a| a < n))
+	}
+
+	/*
+	Puts the puzzles into the world: each barrier goes in the room its
+	passage leaves from, each key in its room, and the passage's exit
+	(in exits, which runs alongside passages) is blocked by the barrier
+	*/
+	pub fn apply(&self, rng: &mut Rng, passages: &[Passage], locations: &mut [Location], exits: &mut [ExitBuilder]) {
+		let mut kinds: Vec<usize> = (0..BARRIERS.len()).collect();
+		let mut keys: Vec<usize> = (0..KEYS.len()).collect();
+		rng.shuffle(&mut kinds);
+		rng.shuffle(&mut keys);
+		for puzzle in &self.puzzles {
+			let k = kinds.remove(kinds.iter().position(|&k| BARRIERS[k].need == puzzle.need).unwrap());
+			let kind = &BARRIERS[k];
+			let barrier = barrier(rng, kind);
+			exits[puzzle.passage]
+				.set_blocked_by_item(barrier.get_id())
+				.set_blocked_desc(kind.blocked_desc);
+			locations[passages[puzzle.passage].from].add_item(barrier);
+			if let Some(room) = puzzle.key_room {
+				let k = keys.remove(keys.iter().position(|&k| KEYS[k].0 == puzzle.need).unwrap());
+				locations[room].add_item(key(rng, k));
+			}
+		}
+	}
+}
+
+/*
+Returns, for each room, whether it can be reached from the entrance
+without going through any of the closed passages
+*/
+fn reachable_rooms(passages: &[Passage], closed: &[usize], room_count: usize) -> Vec<bool> {
+	let mut reachable = vec![false; room_count];
+	reachable[0] = true;
+	for (p, passage) in passages.iter().enumerate() {
+		if reachable[passage.from] && !closed.contains(&p) {
+			reachable[passage.to] = true;
+		}
+	}
+	reachable
+}
+
+/*
+Returns the passages that lead from the entrance to room
+*/
+fn route_to(passages: &[Passage], room: usize) -> Vec<usize> {
+	let mut route = Vec::new();
+	let mut current = room;
+	while let Some(p) = passages.iter().position(|passage| passage.to == current) {
+		route.push(p);
+		current = passages[p].from;
+	}
+	route
+}
+
+fn barrier(rng: &mut Rng, kind: &BarrierKind) -> Item {
+	let mut builder = ItemBuilder::new(kind.i_type, kind.name, kind.desc);
+	builder.set_id(rng.id()).set_fixed(true);
+	match kind.need {
+		Need::DAMAGE(ref d) => {
+			builder.set_damaged_by(d.clone())
+				.set_health(if *d == DamageType::CUTTING { 1 } else { rng.between(2, 3) as i32 })
+				.set_destroyed_desc(kind.destroyed_desc)
+				.set_destroy_points(10);
+		},
+		Need::ATTACHMENT => {
+			builder.set_can_attach(true);
+		},
+	}
+	builder.finalize()
+}
+
+fn key(rng: &mut Rng, k: usize) -> Item {
+	let (ref need, i_type, name, desc) = KEYS[k];
+	let mut builder = ItemBuilder::new(i_type, name, desc);
+	builder.set_id(rng.id());
+	match *need {
+		Need::DAMAGE(ref d) => {
+			builder.set_damage_type(d.clone());
+		},
+		Need::ATTACHMENT => {
+			builder.set_can_attach(true);
+		},
+	}
+	if i_type == "pickaxe" {
+		builder.add_alias("pick");
+	}
+	builder.finalize()
+}
@@ -0,0 +1,337 @@
+/*
+Stories for generated worlds.
+
+A story gives a generated temple its reason for being played: what the
+player has come to do, and what haunts the place. The objective is one
+of retrieving a treasure from the sanctum, rescuing someone held there,
+or escaping from deep inside the temple to its gate. The theme is the
+temple's danger, like a serpent cult or rising water, and colours the
+intro, the ending and the rooms along the way.
+
+All the text is written from templates. A template names the parts of
+the story in braces, like {temple} or {goal}, and fill puts them in,
+with a capital letter when one starts a sentence.
+*/
+
+use game::ending::{Condition,Ending,EndingKind};
+use game::rng::Rng;
+use obj::item::ItemBuilder;
+use obj::location::Location;
+
+pub const NO_EXIT: &str = "Solid stone blocks the way.";
+
+/*
+What the player has come to do
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+	RETRIEVE, // Take the treasure from the sanctum
+	RESCUE, // Reach the captive in the sanctum
+	ESCAPE, // Get from the start to the gate
+}
+
+/*
+A temple's danger:
+	temple: what the temple is called
+	omen: what the intro warns of
+	farewell: the last line of the ending
+	relic: what it has done to the treasure
+	captive: what it has done to the captive
+	details: things it has left in the rooms
+*/
+struct Theme {
+	temple: &'static str,
+	omen: &'static str,
+	farewell: &'static str,
+	relic: &'static str,
+	captive: &'static str,
+	details: [&'static str; 6],
+}
+
+const THEMES: [Theme; 4] = [
+	Theme {
+		temple: "the Temple of the Coiled Serpent",
+		omen: "The serpent cult that built it is said to guard it still.",
+		farewell: "Somewhere in the dark below, something vast stirs in its sleep.",
+		relic: "A serpent is coiled around its base.",
+		captive: "The cult's ropes still bind their wrists.",
+		details: [
+			"A carving of a coiled serpent watches you from above the doorway.",
+			"A dry snakeskin lies in the corner.",
+			"Something slithers away into a crack as you come in.",
+			"Serpent heads carved into the walls bare their stone fangs.",
+			"Fresh flowers lie at the foot of one wall. Someone has been here recently.",
+			"A faint hissing echoes from somewhere inside the walls.",
+		],
+	},
+	Theme {
+		temple: "the Temple of the Rain God",
+		omen: "The river has been rising through its lower halls for years.",
+		farewell: "Far below, the water goes on rising.",
+		relic: "The water has polished it to a shine.",
+		captive: "They are soaked through and shivering.",
+		details: [
+			"Water pools ankle deep across the floor.",
+			"A line of dark silt runs around the walls at waist height.",
+			"Water trickles down the walls and gathers in the cracks.",
+			"The floor is slick with green slime.",
+			"You can hear the distant roar of rushing water.",
+			"Drowned roots hang from the ceiling, dripping.",
+		],
+	},
+	Theme {
+		temple: "the Temple of the Nine Kings",
+		omen: "The people of the valley say its dead priests still walk its halls.",
+		farewell: "The whispers that followed you fall silent at last.",
+		relic: "It is as cold as ice to the touch.",
+		captive: "Their eyes are wide with whatever they have seen down here.",
+		details: [
+			"The air here is bitterly cold.",
+			"Whispers seem to follow you, but they stop whenever you listen.",
+			"Scratches cover the walls, as if something tried to claw its way out.",
+			"A skeleton in the rags of a priest's robe slumps against the wall.",
+			"The shadows seem to move when you aren't looking at them.",
+			"Someone has drawn warding signs in ash across the floor.",
+		],
+	},
+	Theme {
+		temple: "the Temple of the Pale Moon",
+		omen: "A pale fungus has crept through every hall, and the air is thick with its spores.",
+		farewell: "Behind you, the spores drift on through the dark, slow and patient.",
+		relic: "Not a speck of the fungus has touched it.",
+		captive: "Pale spores dust their hair and clothes.",
+		details: [
+			"A pale fungus carpets the floor, soft underfoot.",
+			"Clusters of white mushrooms sprout from cracks in the walls.",
+			"Spores hang in the air, glittering faintly.",
+			"The walls are furred with grey mould.",
+			"A sweet, rotten smell hangs in the air.",
+			"Fungus has swallowed a fallen statue whole, leaving only its outline.",
+		],
+	},
+];
+
+/*
+Rooms other than the first and the last: name, description. There is
+one for every room the largest temple can have.
+*/
+const ROOMS: [(&str, &str); 17] = [
+	("Antechamber", "You stand in a low antechamber. Faded reliefs of kneeling figures cover the walls."),
+	("Gallery", "A long gallery stretches out around you, lined with empty plinths."),
+	("Crypt", "Stone coffins rest in alcoves along the walls of this cold crypt."),
+	("Shrine", "A small shrine, its altar bare. Soot from old offerings stains the ceiling."),
+	("Colonnade", "Twin rows of pillars march off into the gloom."),
+	("Hall of Pillars", "Thick pillars carved like tree trunks hold up the ceiling of this hall."),
+	("Ossuary", "Skulls and bones are stacked neatly in niches from floor to ceiling."),
+	("Guardroom", "Rusted spear heads and broken shields litter the floor of this old guardroom."),
+	("Cistern", "Water drips into a dark pool in the middle of the floor."),
+	("Scriptorium", "Crumbling shelves line the walls, and scraps of bark paper crunch underfoot."),
+	("Vestibule", "A narrow vestibule, its floor worn smooth by countless feet."),
+	("Painted Chamber", "Brightly painted figures march across every wall of this chamber."),
+	("Robing Room", "Stone benches line the walls, and rotted cloth hangs from bronze pegs."),
+	("Well Shaft", "A round chamber around a deep, dry well. Cool air rises from below."),
+	("Mural Hall", "A faded mural of a great procession runs the length of this hall."),
+	("Storeroom", "Cracked clay jars, taller than a man, lean against one another."),
+	("Sunken Court", "Steps lead down into a small court, open to a sliver of sky far above."),
+];
+
+/*
+Treasures that can wait in the sanctum: type, name, description
+*/
+const TREASURES: [(&str, &str, &str); 4] = [
+	("idol", "a small idol", "The idol is in the shape of a man and appears to be made entirely of gold."),
+	("chalice", "a jeweled chalice", "A golden chalice, its rim set with rubies."),
+	("mask", "a jade mask", "A funeral mask carved from a single piece of green jade."),
+	("crown", "a feathered crown", "A crown of beaten gold, hung with long green feathers."),
+];
+
+/*
+People who can be held in the sanctum: type, name, description
+*/
+const CAPTIVES: [(&str, &str, &str); 3] = [
+	("professor", "Professor Ames", "The professor is thin and pale, but alive."),
+	("guide", "your guide", "Your guide looks up at you in disbelief."),
+	("explorer", "a lost explorer", "The explorer's clothes hang in tatters."),
+];
+
+/*
+The text for each objective: title, intro, first room (name,
+description), last room (name, description), ending (name, text)
+*/
+struct Plot {
+	title: &'static str,
+	intro: &'static str,
+	first: (&'static str, &'static str),
+	last: (&'static str, &'static str),
+	ending: (&'static str, &'static str),
+}
+
+const RETRIEVE_PLOT: Plot = Plot {
+	title: "The Treasure of {temple}",
+	intro: "Legends speak of {goal}, hidden in the heart of {temple}. {omen} You have come to claim it all the same.",
+	first: ("Temple Entrance", "You stand just inside the entrance to {temple}. Daylight spills in behind you."),
+	last: ("Inner Sanctum", "You have reached the heart of {temple}. A stone altar stands in the middle of the room."),
+	ending: ("The Treasure", "You lift {goal} from the altar. The treasure of {temple} is yours. {farewell}"),
+};
+
+const RESCUE_PLOT: Plot = Plot {
+	title: "Lost in {temple}",
+	intro: "{goal} went into {temple} three days ago and never came back. {omen} You have come to bring them home.",
+	first: ("Temple Entrance", "You stand just inside the entrance to {temple}. Daylight spills in behind you."),
+	last: ("Inner Sanctum", "You have reached the heart of {temple}. {goal} lies bound beside a stone altar."),
+	ending: ("The Rescue", "You untie {goal}, and together you make your way back out into the daylight. {farewell}"),
+};
+
+const ESCAPE_PLOT: Plot = Plot {
+	title: "Escape from {temple}",
+	intro: "You wake on cold stone with a throbbing head, somewhere deep inside {temple}. {omen} The way you came in has fallen, and you must find another way out.",
+	first: ("Fallen Passage", "Broken stone fills the passage behind you, where the roof came down."),
+	last: ("Temple Gate", "Daylight streams through the great gate of {temple}."),
+	ending: ("The Escape", "You stumble through the gate and out into the daylight. {farewell}"),
+};
+
+/*
+A generated world's story. The intro is shown before the game begins.
+*/
+#[derive(Debug, Clone)]
+pub struct Story {
+	pub objective: Objective,
+	pub title: String,
+	pub intro: String,
+	theme: usize,
+	goal: (&'static str, &'static str, &'static str), // The treasure or captive: type, name, description
+}
+
+impl Story {
+	/*
+	Chooses an objective and a theme
+	*/
+	pub fn generate(rng: &mut Rng) -> Story {
+		let objective = *rng.choose(&[Objective::RETRIEVE, Objective::RESCUE, Objective::ESCAPE]);
+		let mut story = Story {
+			objective,
+			title: String::new(),
+			intro: String::new(),
+			theme: rng.below(THEMES.len()),
+			goal: match objective {
+				Objective::RETRIEVE => *rng.choose(&TREASURES),
+				Objective::RESCUE => *rng.choose(&CAPTIVES),
+				Objective::ESCAPE => ("", "", ""),
+			},
+		};
+		story.title = story.fill(story.plot().title);
+		story.intro = story.fill(story.plot().intro);
+		story
+	}
+
+	/*
+	Creates room_count locations. The player starts in the first, and
+	goal is the last one they need to reach. The rest are named from
+	ROOMS, and some have a detail from the theme.
+	*/
+	pub fn locations(&self, rng: &mut Rng, room_count: usize, goal: usize) -> Vec<Location> {
+		let mut names: Vec<usize> = (0..ROOMS.len()).collect();
+		rng.shuffle(&mut names);
+		let mut details = THEMES[self.theme].details.to_vec();
+		rng.shuffle(&mut details);
+		let plot = self.plot();
+		(0..room_count).map(|r| match r {
+			0 => Location::new(plot.first.0, &self.fill(plot.first.1), NO_EXIT),
+			r if r == goal => Location::new(plot.last.0, &self.fill(plot.last.1), NO_EXIT),
+			r => {
+				let (name, desc) = ROOMS[names[r]];
+				match details.pop() {
+					Some(detail) if rng.chance(75) => Location::new(name, &format!("{} {}", desc, detail), NO_EXIT),
+					_ => Location::new(name, desc, NO_EXIT),
+				}
+			},
+		}).collect()
+	}
+
+	/*
+	Puts the treasure or captive in the goal location, and returns the
+	ending for winning
+	*/
+	pub fn finish(&self, rng: &mut Rng, locations: &mut [Location], goal: usize) -> Ending {
+		let theme = &THEMES[self.theme];
+		let plot = self.plot();
+		let mut ending = Ending::new(plot.ending.0, &self.fill(plot.ending.1), EndingKind::WIN);
+		let (i_type, name, desc) = self.goal;
+		match self.objective {
+			Objective::RETRIEVE => {
+				let treasure = ItemBuilder::new(i_type, name, &format!("{} {}", desc, theme.relic))
+					.set_id(rng.id())
+					.set_take_points(50)
+					.finalize();
+				ending.add_condition(Condition::HOLDING(treasure.get_id()));
+				locations[goal].add_item(treasure);
+			},
+			Objective::RESCUE => {
+				locations[goal].add_item(ItemBuilder::new(i_type, name, &format!("{} {}", desc, theme.captive))
+					.set_id(rng.id())
+					.set_scenery(true)
+					.finalize());
+				locations[goal].points = 50;
+				ending.add_condition(Condition::REACHED(goal));
+			},
+			Objective::ESCAPE => {
+				locations[goal].points = 50;
+				ending.add_condition(Condition::REACHED(goal));
+			},
+		}
+		ending
+	}
+
+	fn plot(&self) -> &'static Plot {
+		match self.objective {
+			Objective::RETRIEVE => &RETRIEVE_PLOT,
+			Objective::RESCUE => &RESCUE_PLOT,
+			Objective::ESCAPE => &ESCAPE_PLOT,
+		}
+	}
+
+	/*
+	Fills in a template. A part that starts a sentence gets a capital
+	letter.
+	*/
+	fn fill(&self, template: &str) -> String {
+		let theme = &THEMES[self.theme];
+		let parts = [
+			("{temple}", theme.temple),
+			("{goal}", self.goal.1),
+			("{omen}", theme.omen),
+			("{farewell}", theme.farewell),
+		];
+		let mut text = String::new();
+		let mut rest = template;
+		while let Some(start) = rest.find('{') {
+			text.push_str(&rest[..start]);
+			rest = &rest[start..];
+			match parts.iter().find(|&&(key, _)| rest.starts_with(key)) {
+				Some(&(key, value)) => {
+					let trimmed = text.trim_end();
+					if trimmed.is_empty() || trimmed.ends_with('.') {
+						text.push_str(&capitalize(value));
+					} else {
+						text.push_str(value);
+					}
+					rest = &rest[key.len()..];
+				},
+				None => {
+					text.push('{');
+					rest = &rest[1..];
+				},
+			}
+		}
+		text.push_str(rest);
+		text
+	}
+}
+
+fn capitalize(s: &str) -> String {
+	let mut chars = s.chars();
+	match chars.next() {
+		Some(c) => c.to_uppercase().chain(chars).collect(),
+		None => String::new(),
+	}
+}
@@ -0,0 +1,188 @@
+/*
+Checks a world for mistakes that would otherwise only show up in play.
+
+Errors are things that break the game:
+	an exit leading to a location that doesn't exist
+	an exit blocked by an item that isn't in the exit's location (the
+	exit would never be blocked)
+	a door locked by a key that isn't anywhere in the world, or by no key
+	at all
+	an item or door the player can't name, because its type and aliases
+	are reserved words or taken by other items
+	a description with a mistake in its template tags (see template.rs)
+Warnings are things that are usually mistakes:
+	a location that can't be reached from the start
+	a one-way exit, with no way back from where it leads
+*/
+
+use std::collections::{HashSet,VecDeque};
+use std::fmt;
+
+use obj::ObjId;
+use obj::location::Location;
+use obj::template;
+use parsing::TokenType;
+use parsing::lexicon::Lexicon;
+use parsing::token::Token;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+	ERROR, WARNING,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+	pub severity: Severity,
+	pub message: String,
+}
+
+impl Problem {
+	fn error(message: String) -> Problem {
+		Problem {
+			severity: Severity::ERROR,
+			message,
+		}
+	}
+
+	fn warning(message: String) -> Problem {
+		Problem {
+			severity: Severity::WARNING,
+			message,
+		}
+	}
+}
+
+impl fmt::Display for Problem {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}: {}", match self.severity {
+			Severity::ERROR => "error",
+			Severity::WARNING => "warning",
+		}, self.message)
+	}
+}
+
+/*
+Checks the locations of a world where the player starts in location
+start. Returns every problem found, errors first.
+*/
+pub fn validate(locations: &[Location], start: usize) -> Vec<Problem> {
+	let mut problems = Vec::new();
+	if start >= locations.len() {
+		problems.push(Problem::error(format!("the player starts in location {}, but there are only {}", start, locations.len())));
+		return problems;
+	}
+	check_exits(locations, &mut problems);
+	check_items(locations, &mut problems);
+	check_descriptions(locations, &mut problems);
+	check_reachable(locations, start, &mut problems);
+	check_one_way(locations, &mut problems);
+	problems.sort_by_key(|p| p.severity == Severity::WARNING);
+	problems
+}
+
+/*
+Returns true if any of the problems is an error
+*/
+pub fn has_errors(problems: &[Problem]) -> bool {
+	problems.iter().any(|p| p.severity == Severity::ERROR)
+}
+
+fn check_exits(locations: &[Location], problems: &mut Vec<Problem>) {
+	for location in locations {
+		for (d, exit) in location.exits() {
+			if exit.dest >= locations.len() {
+				problems.push(Problem::error(format!("{}: the exit {} leads to location {}, which doesn't exist", location.name, d.name(), exit.dest)));
+			}
+			if let Some(ObjId::ITEMID(id)) = exit.blocked_by {
+				if location.find_item_by_id(id).is_none() {
+					problems.push(Problem::error(format!("{}: the exit {} is blocked by an item that isn't in {}", location.name, d.name(), location.name)));
+				}
+			}
+			if let Some(ref door) = exit.door {
+				match door.key {
+					Some(key) if !locations.iter().any(|l| l.find_item_by_id(key).is_some()) => {
+						problems.push(Problem::error(format!("{}: the key to the {} {} isn't anywhere in the world", location.name, door.i_type, d.name())));
+					}
+					None if door.is_locked => {
+						problems.push(Problem::error(format!("{}: the {} {} is locked and has no key", location.name, door.i_type, d.name())));
+					}
+					_ => (),
+				}
+			}
+		}
+	}
+}
+
+/*
+Every item and door must be reachable by at least one of its nouns, as the
+parser would see it with the world's lexicon
+*/
+fn check_items(locations: &[Location], problems: &mut Vec<Problem>) {
+	let lexicon = Lexicon::from_locations(locations);
+	for location in locations {
+		for item in location.all_items() {
+			let nouns = Some(&item.i_type).into_iter().chain(item.aliases.iter());
+			let reachable = nouns.map(|n| n.to_lowercase()).any(|n| {
+				Token::with_lexicon(&n, &lexicon).t_type == TokenType::ITEM(item.i_type.clone())
+			});
+			if !reachable {
+				problems.push(Problem::error(format!("{}: the player has no way to refer to {} (type '{}')", location.name, item.name, item.i_type)));
+			}
+		}
+		for door in location.doors() {
+			let nouns = Some(&door.i_type).into_iter().chain(door.aliases.iter());
+			let reachable = nouns.map(|n| n.to_lowercase()).any(|n| {
+				Token::with_lexicon(&n, &lexicon).t_type == TokenType::ITEM(door.i_type.clone())
+			});
+			if !reachable {
+				problems.push(Problem::error(format!("{}: the player has no way to refer to {} (type '{}')", location.name, door.name, door.i_type)));
+			}
+		}
+	}
+}
+
+fn check_descriptions(locations: &[Location], problems: &mut Vec<Problem>) {
+	for location in locations {
+		if let Err(e) = template::check(&location.desc) {
+			problems.push(Problem::error(format!("{}: the description has a mistake: {}", location.name, e)));
+		}
+		for item in location.all_items() {
+			let descs = [&item.desc, &item.is_on_desc, &item.light_damage_desc, &item.heavy_damage_desc, &item.destroyed_desc];
+			if let Some(e) = descs.iter().filter_map(|d| template::check(d).err()).next() {
+				problems.push(Problem::error(format!("{}: the description of {} has a mistake: {}", location.name, item.name, e)));
+			}
+		}
+	}
+}
+
+fn check_reachable(locations: &[Location], start: usize, problems: &mut Vec<Problem>) {
+	let mut seen: HashSet<usize> = HashSet::new();
+	let mut queue: VecDeque<usize> = VecDeque::new();
+	seen.insert(start);
+	queue.push_back(start);
+	while let Some(l) = queue.pop_front() {
+		for (_, exit) in locations[l].exits() {
+			if exit.dest < locations.len() && seen.insert(exit.dest) {
+				queue.push_back(exit.dest);
+			}
+		}
+	}
+	for (l, location) in locations.iter().enumerate() {
+		if !seen.contains(&l) {
+			problems.push(Problem::warning(format!("{}: can't be reached from {}", location.name, locations[start].name)));
+		}
+	}
+}
+
+/*
+An exit is one-way if nothing in the location it leads to leads back
+*/
+fn check_one_way(locations: &[Location], problems: &mut Vec<Problem>) {
+	for (l, location) in locations.iter().enumerate() {
+		for (d, exit) in location.exits() {
+			if exit.dest < locations.len() && !locations[exit.dest].exits().iter().any(|&(_, e)| e.dest == l) {
+				problems.push(Problem::warning(format!("{}: the exit {} to {} is one-way", location.name, d.name(), locations[exit.dest].name)));
+			}
+		}
+	}
+}
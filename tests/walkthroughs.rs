@@ -0,0 +1,997 @@
+/*
+Plays every walkthrough in worlds/ against the world it sits beside.
+NAME.walkthrough and NAME.anything.walkthrough both play NAME.world.
+*/
+
+extern crate rustade;
+
+use std::fs;
+use std::panic;
+use std::path::Path;
+
+use rustade::game::engine::{Engine,GameStatus};
+use rustade::game::gamestate::GameState;
+use rustade::game::rng::Rng;
+use rustade::game::solver;
+use rustade::game::walkthrough::{run_walkthrough,run_walkthrough_file};
+use rustade::obj::door::Door;
+use rustade::obj::item::ItemBuilder;
+use rustade::obj::location::{ExitBuilder,Location};
+use rustade::obj::template::{self,Context,Scope};
+use rustade::parsing::DirectionType;
+use rustade::world::{World,builder,generator,loader,validator};
+use rustade::world::generator::{Passage,Settings};
+use rustade::world::puzzle::PuzzleGraph;
+use rustade::world::story::{Objective,Story};
+
+#[test]
+fn world_walkthroughs() {
+	let worlds = Path::new(env!("CARGO_MANIFEST_DIR")).join("worlds");
+	let mut failures = Vec::new();
+	let mut count = 0;
+	for entry in fs::read_dir(&worlds).unwrap() {
+		let path = entry.unwrap().path();
+		let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+		if !file_name.ends_with(".walkthrough") {
+			continue;
+		}
+		count += 1;
+		let world_name = file_name.split('.').next().unwrap();
+		let world_path = worlds.join(format!("{}.world", world_name));
+		let result = match loader::load_world_file(&world_path.to_string_lossy()) {
+			Ok(world) => run_walkthrough_file(&path.to_string_lossy(), world),
+			Err(e) => Err(format!("{}: {}", world_path.display(), e)),
+		};
+		if let Err(e) = result {
+			failures.push(e);
+		}
+	}
+	assert!(count > 0, "no walkthroughs found in {}", worlds.display());
+	assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
+
+#[test]
+fn failed_check_is_reported() {
+	let script = "> e\nroom Outside the Temple\n";
+	let result = run_walkthrough(script, builder::build_fixed_world());
+	let e = result.unwrap_err();
+	assert!(e.contains("line 2, after 'e'"), "{}", e);
+	assert!(e.contains("in 'Entrance'"), "{}", e);
+}
+
+#[test]
+fn endings_are_checked_in_order() {
+	let world = loader::load_world("
+[location start]
+name = Start
+[location goal]
+name = Goal
+[exit start north]
+dest = goal
+[item rock]
+location = goal
+name = a rock
+[ending goal]
+kind = neutral
+text = You made it.
+reached = goal
+[ending late]
+kind = loss
+turns = 2
+").unwrap();
+	let script = "
+> look
+status playing
+> inventory
+contains *** You have lost ***
+ending late
+";
+	run_walkthrough(script, world).unwrap();
+
+	let world = loader::load_world("
+[location start]
+name = Start
+[location goal]
+name = Goal
+[exit start north]
+dest = goal
+[ending goal]
+kind = neutral
+reached = goal
+").unwrap();
+	run_walkthrough("> n\nstatus ended\ncontains *** The End ***\n", world).unwrap();
+}
+
+#[test]
+fn entering_a_hazard_kills() {
+	let world = loader::load_world("
+[location start]
+name = Start
+[location cave]
+name = Cave
+[exit start north]
+dest = cave
+[exit cave south]
+dest = start
+[item sword]
+location = start
+name = a sword
+[hazard collapse]
+name = Buried
+text = The roof of the cave falls in.
+enter = cave
+holding = sword
+").unwrap();
+	let script = "
+> n
+status playing
+> s
+> take sword
+> n
+contains The roof of the cave falls in.
+contains *** You have died ***
+> quit
+status quit
+";
+	run_walkthrough(script, world).unwrap();
+}
+
+/*
+Plays commands in a world, saves, then restores into the world loaded
+anew, as if the game had been quit and started again
+*/
+fn restore_fresh(text: &str, commands: &[&str], save: &str) -> Engine {
+	let mut engine = Engine::new(GameState::from_world(loader::load_world(text).unwrap()));
+	engine.set_save_dir(&std::env::temp_dir());
+	engine.start();
+	for c in commands {
+		engine.step(c);
+	}
+	engine.step(&format!("save {}", save));
+	let mut engine = Engine::new(GameState::from_world(loader::load_world(text).unwrap()));
+	engine.set_save_dir(&std::env::temp_dir());
+	engine.start();
+	let restored = engine.step(&format!("restore {}", save));
+	let _ = fs::remove_file(std::env::temp_dir().join(save));
+	assert!(restored.text.iter().any(|t| t.starts_with("Restored from")), "{:?}", restored.text);
+	engine
+}
+
+#[test]
+fn saves_restore_into_a_fresh_world() {
+	let text = "
+[location hall]
+name = Hall
+[location shrine]
+name = Shrine
+[exit hall north]
+dest = shrine
+[exit shrine south]
+dest = hall
+[item idol]
+location = shrine
+name = a small idol
+[ending treasure]
+kind = win
+text = The idol is yours.
+holding = idol
+";
+	let mut engine = restore_fresh(text, &["n"], "rustade-fresh-world.sav");
+	let looked = engine.step("look");
+	assert!(looked.text.iter().any(|t| t.contains("Shrine")), "{:?}", looked.text);
+	let taken = engine.step("take idol");
+	assert!(taken.text.iter().any(|t| t.contains("The idol is yours.")), "{:?}", taken.text);
+	assert_eq!(engine.status(), GameStatus::WON);
+}
+
+#[test]
+fn save_files_are_named_and_checked() {
+	let text = "[location hall]\nname = Hall\n";
+	let dir = std::env::temp_dir();
+	let mut engine = Engine::new(GameState::from_world(loader::load_world(text).unwrap()));
+	engine.set_save_dir(&dir);
+	engine.start();
+
+	// The file name keeps its case
+	let saved = engine.step("save RustADE-Named.sav");
+	assert_eq!(saved.text, vec!["Saved to RustADE-Named.sav.".to_string()]);
+	assert!(fs::read_dir(&dir).unwrap().any(|e| e.unwrap().file_name() == "RustADE-Named.sav"));
+
+	// Only a name, not a path
+	for name in &["../escaped.sav", "sub/dir.sav", "..", "C:escaped.sav"] {
+		let saved = engine.step(&format!("save {}", name));
+		assert!(saved.text[0].contains("isn't a file name"), "{:?}", saved.text);
+		assert!(saved.text[0].contains("The game was not saved."), "{:?}", saved.text);
+		let restored = engine.step(&format!("restore {}", name));
+		assert!(restored.text[0].contains("The game was not restored."), "{:?}", restored.text);
+	}
+
+	// And only into the world it was saved in
+	let mut other = Engine::new(GameState::from_world(loader::load_world("[location attic]\nname = Attic\n").unwrap()));
+	other.set_save_dir(&dir);
+	other.start();
+	let restored = other.step("restore RustADE-Named.sav");
+	let _ = fs::remove_file(dir.join("RustADE-Named.sav"));
+	assert!(restored.text[0].contains("The save file is from a different world."), "{:?}", restored.text);
+	assert!(restored.text[0].contains("The game was not restored."), "{:?}", restored.text);
+}
+
+#[test]
+fn items_are_damaged_where_they_are() {
+	let world = loader::load_world("
+[location workshop]
+name = Workshop
+[item bench]
+location = workshop
+name = a bench
+fixed = true
+has_surface = true
+[item vase]
+on = bench
+name = a clay vase
+desc = A plain clay vase.
+health = 2
+damaged_by = smashing
+light_damage_desc = The vase is cracked.
+[item idol]
+location = workshop
+name = a stone idol
+scenery = true
+desc = A stone idol leers at you.
+health = 2
+damaged_by = smashing
+light_damage_desc = The idol's nose has broken off.
+[item hammer]
+location = workshop
+name = a hammer
+damage_type = smashing
+").unwrap();
+	let script = "
+> take hammer
+> use hammer
+contains You could smash the vase or the idol with the hammer. Say which, as in \"hit vase with hammer\".
+> hit vase with hammer
+contains You smash the vase with the hammer.
+> look on bench
+contains On the bench is a clay vase.
+> examine vase
+contains The vase is cracked.
+> hit idol with hammer
+> examine idol
+contains The idol's nose has broken off.
+> hit vase with hammer
+> use hammer
+contains You smash the idol with the hammer.
+";
+	run_walkthrough(script, world).unwrap();
+}
+
+#[test]
+fn use_only_ties_to_a_clear_target() {
+	let world = loader::load_world("
+[location dock]
+name = Dock
+[location pier]
+name = Pier
+[exit dock east]
+dest = pier
+[exit pier west]
+dest = dock
+[item rope]
+location = dock
+name = a rope
+can_attach = true
+[item post]
+location = dock
+name = a wooden post
+fixed = true
+can_attach = true
+[item ring]
+location = pier
+name = an iron ring
+fixed = true
+can_attach = true
+[item hook]
+location = pier
+name = a rusty hook
+fixed = true
+can_attach = true
+[item cord]
+location = pier
+name = a cord
+can_attach = true
+").unwrap();
+	let script = "
+> use rope
+contains You aren't carrying a rope.
+> take rope
+> e
+> use rope
+contains You could tie the rope to the ring or the hook. Say which, as in \"tie rope to ring\".
+> tie rope to hook
+contains You tie the rope to the hook.
+> take cord
+> use cord
+contains You tie the cord to the ring.
+> w
+> take rope
+contains You don't see a rope here.
+";
+	run_walkthrough(script, world).unwrap();
+}
+
+#[test]
+fn jump_hazards_survive_a_fresh_restore() {
+	let text = "
+[location ledge]
+name = Ledge
+[item chasm]
+location = ledge
+name = a chasm
+scenery = true
+[hazard fall]
+name = Fallen
+text = You fall into the chasm.
+jump = chasm
+";
+	let mut engine = restore_fresh(text, &["look"], "rustade-fresh-jump.sav");
+	let jumped = engine.step("jump into chasm");
+	assert!(jumped.text.iter().any(|t| t.contains("You fall into the chasm.")), "{:?}", jumped.text);
+	assert_eq!(engine.status(), GameStatus::LOST);
+}
+
+#[test]
+fn light_sources_burn_out() {
+	let world = loader::load_world("
+[location cellar]
+name = Cellar
+desc = Damp stone walls.
+dark = true
+[item torch]
+location = cellar
+name = a torch
+can_turn_on = true
+gives_light = true
+fuel = 4
+is_on = true
+").unwrap();
+	let script = "
+contains Damp stone walls.
+> look
+contains The torch is flickering.
+> look
+> look
+> look
+contains The torch flickers and goes out.
+> look
+contains It is pitch black.
+> light torch
+contains The torch has run out of fuel.
+> use torch
+contains The torch has run out of fuel.
+lacks You turn
+";
+	run_walkthrough(script, world).unwrap();
+}
+
+#[test]
+fn only_some_things_turn_on() {
+	let world = loader::load_world("
+[location shed]
+name = Shed
+[item rope]
+location = shed
+name = a rope
+[item lantern]
+location = shed
+name = a lantern
+can_turn_on = true
+[item sack]
+location = shed
+name = a sack
+has_inside = true
+").unwrap();
+	let script = "
+> turn off rope
+contains You can't turn that off.
+> turn on rope
+contains You can't turn that on.
+> turn off lantern
+contains The lantern is already off.
+> turn on lantern
+contains You turn on the lantern.
+> take lantern
+> put lantern in sack
+> take sack
+> use lantern
+contains You turn off the lantern.
+> look in sack
+contains a lantern
+";
+	run_walkthrough(script, world).unwrap();
+}
+
+#[test]
+fn timed_events_survive_save_and_restore() {
+	let world = loader::load_world("
+[location vault]
+name = Vault
+desc = Gold glitters on every shelf.
+[location corridor]
+name = Corridor
+[location outside]
+name = Outside
+[exit vault north]
+dest = corridor
+[exit corridor north]
+dest = outside
+[exit corridor south]
+dest = vault
+[exit outside south]
+dest = corridor
+[item gem]
+location = vault
+name = a gem
+[event rumble]
+daemon = 1
+holding = gem
+text = The ground shakes.
+[event collapse]
+fuse = 3
+holding = gem
+text = With a roar, the vault caves in!
+location = vault
+desc = Rubble fills the vault.
+sets_flag = collapsed
+cancels = rumble
+[hazard crushed]
+flag = collapsed
+reached = vault
+text = You are buried under the rubble.
+[ending escaped]
+kind = win
+flag = collapsed
+reached = outside
+").unwrap();
+	let save = "rustade-timed-events.sav";
+	let script = format!("
+> take gem
+lacks The ground shakes.
+> save {0}
+> look
+contains The ground shakes.
+> look
+contains The ground shakes.
+> look
+contains the vault caves in!
+contains You are buried under the rubble.
+status lost
+> restore {0}
+contains Gold glitters on every shelf.
+> n
+contains The ground shakes.
+> n
+contains The ground shakes.
+status playing
+> s
+contains the vault caves in!
+status playing
+> s
+contains Rubble fills the vault.
+lacks The ground shakes.
+status lost
+> undo
+> n
+status won
+", save);
+	let result = run_walkthrough(&script, world);
+	let _ = fs::remove_file(std::env::temp_dir().join(save));
+	result.unwrap();
+}
+
+#[test]
+fn destroy_effects_survive_a_fresh_restore() {
+	let text = "
+[location square]
+name = Square
+[item statue]
+location = square
+name = a statue
+fixed = true
+destroyed_desc = The statue lies in pieces.
+[event quake]
+fuse = 2
+text = The ground heaves.
+destroys = statue
+[ending fallen]
+kind = neutral
+text = The statue has fallen.
+destroyed = statue
+";
+	let mut engine = restore_fresh(text, &[], "rustade-fresh-destroy.sav");
+	engine.step("look");
+	let quake = engine.step("look");
+	assert!(quake.text.iter().any(|t| t.contains("The statue has fallen.")), "{:?}", quake.text);
+	assert_eq!(engine.status(), GameStatus::ENDED);
+}
+
+#[test]
+fn locked_doors_need_a_key() {
+	let e = loader::load_world("[location hall]\nname = Hall\n[door gate]\nname = an iron gate\nlocked = true\n").unwrap_err();
+	assert!(e.contains("door 'gate' is locked and has no key"), "{}", e);
+}
+
+#[test]
+fn doors_survive_a_fresh_restore() {
+	let text = "
+[location hall]
+name = Hall
+[location vault]
+name = Vault
+[door gate]
+name = an iron gate
+locked = true
+key = key
+[exit hall north]
+dest = vault
+door = gate
+[exit vault south]
+dest = hall
+door = gate
+[item key]
+location = hall
+name = a key
+";
+	let mut engine = restore_fresh(text, &["take key", "unlock gate", "open gate"], "rustade-fresh-door.sav");
+	let went = engine.step("n");
+	assert!(went.text.iter().any(|t| t.contains("Vault")), "{:?}", went.text);
+}
+
+#[test]
+fn chance_events_follow_the_seed() {
+	let world = loader::load_world("
+[location cave]
+name = Cave
+[location tunnel]
+name = Tunnel
+[exit cave north]
+dest = tunnel
+[exit tunnel south]
+dest = cave
+[event tremor]
+daemon = 1
+chance = 50
+text = The floor shakes.
+").unwrap();
+	let play = |seed: u64, commands: &[&str]| -> Vec<bool> {
+		let mut game_state = GameState::from_world(world.clone());
+		game_state.set_rng(Rng::new(seed));
+		let mut engine = Engine::new(game_state);
+		engine.start();
+		commands.iter()
+			.map(|c| engine.step(c).text.iter().any(|t| t.contains("The floor shakes.")))
+			.collect()
+	};
+	let looks = ["look"; 20];
+	let shakes = play(7, &looks);
+	assert_eq!(shakes, play(7, &looks));
+	assert!(shakes.contains(&true) && shakes.contains(&false), "{:?}", shakes);
+	assert!(shakes != play(8, &looks));
+
+	// The rng is saved, restored, undone and restarted with the game
+	let save = "rustade-chance-events.sav";
+	let mut game_state = GameState::from_world(world.clone());
+	game_state.set_rng(Rng::new(7));
+	let mut engine = Engine::new(game_state);
+	engine.set_save_dir(&std::env::temp_dir());
+	engine.start();
+	let shaken = |engine: &mut Engine, command: &str| engine.step(command).text.iter().any(|t| t.contains("The floor shakes."));
+	assert_eq!(engine.step("seed").text, vec!["The seed is 7.".to_string()]);
+	for _ in 0..5 {
+		shaken(&mut engine, "look");
+	}
+	engine.step(&format!("save {}", save));
+	let after: Vec<bool> = (0..5).map(|_| shaken(&mut engine, "look")).collect();
+	assert_eq!(after, shakes[5..10].to_vec());
+	engine.step(&format!("restore {}", save));
+	let _ = fs::remove_file(std::env::temp_dir().join(save));
+	let again: Vec<bool> = (0..5).map(|_| shaken(&mut engine, "look")).collect();
+	assert_eq!(again, after);
+	let moves = ["north", "south", "north", "south"];
+	let moved: Vec<bool> = moves.iter().map(|m| shaken(&mut engine, m)).collect();
+	for _ in &moves {
+		engine.step("undo");
+	}
+	let redone: Vec<bool> = moves.iter().map(|m| shaken(&mut engine, m)).collect();
+	assert_eq!(redone, moved);
+	engine.step("restart");
+	engine.step("yes");
+	assert_eq!(engine.step("seed").text, vec!["The seed is 7.".to_string()]);
+	assert_eq!(shaken(&mut engine, "look"), shakes[0]);
+
+	let e = loader::load_world("[location cave]\nname = Cave\n[event tremor]\ndaemon = 1\nchance = 0\n").unwrap_err();
+	assert!(e.contains("expected a percent from 1 to 100, found '0'"), "{}", e);
+}
+
+#[test]
+fn descriptions_follow_the_game() {
+	let world = loader::load_world("
+[location hall]
+name = Hall
+desc = {if first}You step into a vast hall.{else}The hall again.{end}{if here lamp} {The lamp} sits on a table.{end}{if flag rang} The bell still echoes.{end}
+[location yard]
+name = Yard
+desc = A yard, after {turns} turns.{if not turns 7} It is still early.{end}
+[exit hall north]
+dest = yard
+[exit yard south]
+dest = hall
+[item lamp]
+location = hall
+name = an old lamp
+desc = {if on it}{The lamp} glows.{else}{A lamp}, unlit.{end}{if held egg} You hold {an egg} near it.{end}
+can_turn_on = true
+[item egg]
+location = yard
+name = an egg
+[item coins]
+location = yard
+name = some coins
+desc = {A coins}, or {the coins}? {{Braces}} stay.
+[event bell]
+fuse = 2
+text = A bell rings.
+sets_flag = rang
+").unwrap();
+	assert!(world.validate().is_empty(), "{:?}", world.validate());
+	let script = "
+contains You step into a vast hall. The old lamp sits on a table.
+> look
+contains The hall again. The old lamp sits on a table.
+> examine lamp
+contains An old lamp, unlit.
+> turn on lamp
+> examine lamp
+contains The old lamp glows.
+> take lamp
+> n
+contains A yard, after 6 turns. It is still early.
+> examine coins
+contains Some coins, or the coins? {Braces} stay.
+> take egg
+> examine lamp
+contains The old lamp glows. You hold an egg near it.
+> s
+contains The hall again. The bell still echoes.
+lacks sits on a table
+> drop lamp
+> look
+contains The hall again. The old lamp sits on a table.
+";
+	run_walkthrough(script, world).unwrap();
+
+	let broken = loader::load_world("
+[location hall]
+name = Hall
+desc = {if first}Hello.
+[item lamp]
+location = hall
+name = a lamp
+desc = {shine lamp}
+").unwrap();
+	let problems: Vec<String> = broken.validate().iter().map(|p| p.to_string()).collect();
+	assert_eq!(problems, vec![
+		"error: Hall: the description has a mistake: an {if} has no {end}".to_string(),
+		"error: Hall: the description of a lamp has a mistake: {shine lamp} isn't a tag".to_string(),
+	]);
+
+	// A panic while rendering doesn't leave its context behind
+	let text = "{if flag rang}Ringing.{else}Quiet.{end}";
+	let context = Context {
+		flags: vec!["rang".to_string()].into_iter().collect(),
+		..Context::default()
+	};
+	template::with_context(Context::default(), || {
+		let panicked = panic::catch_unwind(|| template::with_context(context, || {
+			assert_eq!(template::render(text, &Scope::default()), "Ringing.");
+			panic!("rendering failed");
+		}));
+		assert!(panicked.is_err());
+		assert_eq!(template::render(text, &Scope::default()), "Quiet.");
+	});
+
+	// Nor can a description be rendered without one by mistake
+	if cfg!(debug_assertions) {
+		assert!(panic::catch_unwind(|| template::render(text, &Scope::default())).is_err());
+	}
+}
+
+#[test]
+fn world_files_validate_cleanly() {
+	let worlds = Path::new(env!("CARGO_MANIFEST_DIR")).join("worlds");
+	for entry in fs::read_dir(&worlds).unwrap() {
+		let path = entry.unwrap().path();
+		if path.extension().and_then(|e| e.to_str()) != Some("world") {
+			continue;
+		}
+		let world = loader::load_world_file(&path.to_string_lossy()).unwrap();
+		let problems: Vec<String> = world.validate().iter().map(|p| p.to_string()).collect();
+		assert!(problems.is_empty(), "{}:\n{}", path.display(), problems.join("\n"));
+	}
+}
+
+#[test]
+fn validator_finds_broken_worlds() {
+	let mut hall = Location::new("Hall", "", "");
+	let mut attic = Location::new("Attic", "", "");
+	let cellar = Location::new("Cellar", "", "");
+	let mut vault = Location::new("Vault", "", "");
+	let boulder = ItemBuilder::new("boulder", "a boulder", "").finalize();
+	let elsewhere = ItemBuilder::new("pebble", "a pebble", "").finalize();
+	let mut door = Door::new("door", "a trapdoor", "");
+	door.key = Some(elsewhere.get_id());
+	hall.add_exit(DirectionType::NORTH, ExitBuilder::new().set_dest(1).set_blocked_by_item(boulder.get_id()).finalize());
+	hall.add_exit(DirectionType::DOWN, ExitBuilder::new().set_dest(7).set_door(&door).finalize());
+	let mut gate = Door::new("gate", "an iron gate", "");
+	gate.is_locked = true;
+	attic.add_exit(DirectionType::EAST, ExitBuilder::new().set_dest(3).set_door(&gate).finalize());
+	vault.add_exit(DirectionType::WEST, ExitBuilder::new().set_dest(1).finalize());
+	attic.add_item(boulder);
+	attic.add_item(ItemBuilder::new("north", "a compass", "").finalize());
+	let problems: Vec<String> = validator::validate(&[hall, attic, cellar, vault], 0).iter().map(|p| p.to_string()).collect();
+	assert_eq!(problems, vec![
+		"error: Hall: the exit north is blocked by an item that isn't in Hall",
+		"error: Hall: the exit down leads to location 7, which doesn't exist",
+		"error: Hall: the key to the door down isn't anywhere in the world",
+		"error: Attic: the gate east is locked and has no key",
+		"error: Attic: the player has no way to refer to a compass (type 'north')",
+		"warning: Cellar: can't be reached from Hall",
+		"warning: Hall: the exit north to Attic is one-way",
+	]);
+}
+
+#[test]
+fn solver_finds_the_shortest_win() {
+	let world = loader::load_world("
+[location shed]
+name = Shed
+[location yard]
+name = Yard
+[location vault]
+name = Vault
+[exit shed south]
+dest = yard
+[exit yard north]
+dest = shed
+[exit yard east]
+dest = vault
+blocked_by = vines
+[exit vault west]
+dest = yard
+[item shears]
+location = shed
+name = some shears
+damage_type = cutting
+[item vines]
+location = yard
+name = tangled vines
+fixed = true
+health = 1
+damaged_by = cutting
+[item crown]
+location = vault
+name = a crown
+[ending crown]
+kind = win
+holding = crown
+").unwrap();
+	let report = solver::solve(world, solver::DEFAULT_MAX_STATES);
+	assert!(report.complete);
+	let solution = ["take shears", "south", "cut vines with shears", "east", "take crown"];
+	assert_eq!(report.solution, Some(solution.iter().map(|c| c.to_string()).collect()));
+	assert!(report.dead_ends.is_empty());
+}
+
+#[test]
+fn solver_finds_dead_ends() {
+	let world = loader::load_world("
+[location landing]
+name = Landing
+[location attic]
+name = Attic
+[location cellar]
+name = Cellar
+[exit landing up]
+dest = attic
+[exit attic down]
+dest = landing
+[exit landing down]
+dest = cellar
+travel_desc = The trapdoor slams shut above you.
+[item coin]
+location = attic
+name = a coin
+[ending coin]
+kind = win
+holding = coin
+").unwrap();
+	let report = solver::solve(world.clone(), solver::DEFAULT_MAX_STATES);
+	assert_eq!(report.solution, Some(vec!["up".to_string(), "take coin".to_string()]));
+	assert_eq!(report.dead_ends, vec![vec!["down".to_string()]]);
+
+	let mut world = world;
+	world.locations[0] = Location::new("Landing", "", "");
+	world.locations[0].add_exit(DirectionType::DOWN, ExitBuilder::new().set_dest(2).finalize());
+	let report = solver::solve(world, solver::DEFAULT_MAX_STATES);
+	assert!(report.complete);
+	assert_eq!(report.solution, None);
+	assert_eq!(report.to_string(), "The world can't be won.\nExplored all 2 positions.");
+}
+
+#[test]
+fn solver_runs_out_of_fuel_and_time() {
+	let world = loader::load_world("
+[location landing]
+name = Landing
+[location hall]
+name = Hall
+dark = true
+[location vault]
+name = Vault
+dark = true
+[exit landing north]
+dest = hall
+[exit hall south]
+dest = landing
+[exit hall north]
+dest = vault
+[exit vault south]
+dest = hall
+[item lantern]
+location = landing
+name = a lantern
+can_turn_on = true
+gives_light = true
+fuel = 4
+[item coin]
+location = vault
+name = a coin
+[ending coin]
+kind = win
+holding = coin
+[ending dusk]
+kind = loss
+turns = 8
+").unwrap();
+	let report = solver::solve(world, solver::DEFAULT_MAX_STATES);
+	assert!(report.complete);
+	let commands = |c: &[&str]| -> Vec<String> { c.iter().map(|c| c.to_string()).collect() };
+	assert_eq!(report.solution, Some(commands(&["take lantern", "north", "north", "turn on lantern", "take coin"])));
+	assert_eq!(report.dead_ends, vec![
+		commands(&["north", "north"]),
+		commands(&["turn on lantern", "north", "north"]),
+		commands(&["north", "south", "turn on lantern", "north"]),
+	]);
+}
+
+/*
+Describes the rooms, exits and items of a world, to compare generated
+worlds by
+*/
+fn layout(world: &World) -> String {
+	let mut layout = String::new();
+	for location in &world.locations {
+		layout.push_str(&format!("{}: {}\n", location.name, location.desc));
+		for (d, exit) in location.exits() {
+			layout.push_str(&format!("\t{} to {}, blocked: {}\n", d.name(), exit.dest, exit.blocked_by.is_some()));
+		}
+		for item in location.all_items() {
+			layout.push_str(&format!("\t{}\n", item.name));
+		}
+	}
+	layout
+}
+
+#[test]
+fn generated_worlds_are_repeatable_and_winnable() {
+	let sizes = [
+		Settings::default(),
+		Settings { depth: 1, branching: 0, puzzles: 1 },
+		Settings { depth: 5, branching: 2, puzzles: 4 },
+	];
+	for settings in &sizes {
+		for seed in 0..10 {
+			let world = generator::generate_with(seed, settings);
+			assert_eq!(layout(&world), layout(&generator::generate_with(seed, settings)), "seed {}", seed);
+			assert!(world.validate().is_empty(), "seed {}: {:?}", seed, world.validate());
+
+			// The goal waits behind the last barrier
+			let goal = settings.depth;
+			let way_in = world.locations.iter()
+				.flat_map(|l| l.exits().into_iter().map(|(_, e)| e))
+				.find(|e| e.dest == goal && e.blocked_by.is_some());
+			assert!(way_in.is_some(), "seed {}: the goal isn't barred", seed);
+
+			let report = solver::solve(world, solver::DEFAULT_MAX_STATES);
+			assert!(report.solution.is_some(), "seed {}:\n{}", seed, report);
+			assert!(report.dead_ends.is_empty(), "seed {}:\n{}", seed, report);
+		}
+	}
+	assert!(layout(&generator::generate(1)) != layout(&generator::generate(2)));
+}
+
+#[test]
+fn generated_worlds_tell_a_story() {
+	let mut objectives = Vec::new();
+	for seed in 0..30 {
+		let world = generator::generate_with(seed, &Settings { depth: 3, branching: 3, puzzles: 1 });
+		let story = Story::generate(&mut Rng::new(seed));
+		if !objectives.contains(&story.objective) {
+			objectives.push(story.objective);
+		}
+		assert!(world.intro.starts_with(&story.title), "seed {}: {}", seed, world.intro);
+		assert!(world.intro.ends_with(&story.intro), "seed {}: {}", seed, world.intro);
+		assert!(!story.intro.contains('{'), "seed {}: {}", seed, story.intro);
+
+		// Every room has its own name, and the rooms on the way are themed
+		let mut names: Vec<&str> = world.locations.iter().map(|l| l.name.as_str()).collect();
+		names.sort();
+		names.dedup();
+		assert_eq!(names.len(), world.locations.len(), "seed {}: {:?}", seed, names);
+		for location in &world.locations {
+			assert!(!location.desc.contains('{'), "seed {}: {}", seed, location.desc);
+		}
+
+		let ending = &world.endings[0];
+		match story.objective {
+			Objective::RETRIEVE => {
+				assert_eq!(ending.name, "The Treasure");
+				assert!(world.locations[3].items().iter().any(|i| i.take_points == 50), "seed {}", seed);
+			},
+			Objective::RESCUE => {
+				assert_eq!(ending.name, "The Rescue");
+				assert_eq!(world.locations[3].points, 50);
+			},
+			Objective::ESCAPE => {
+				assert_eq!(ending.name, "The Escape");
+				assert_eq!(world.locations[3].name, "Temple Gate");
+			},
+		}
+	}
+	assert_eq!(objectives.len(), 3, "{:?}", objectives);
+
+	let mut engine = Engine::new(GameState::from_world(generator::generate(4)));
+	let text = engine.start().text.join("\n");
+	assert!(text.starts_with("Lost in the Temple of the Coiled Serpent\n\nProfessor Ames went into"), "{}", text);
+	assert!(text.contains("Temple Entrance:"), "{}", text);
+}
+
+#[test]
+fn puzzles_are_planned_in_a_solvable_order() {
+	// A route 0-1-2-3, with a branch 1-4-5 off it
+	let passages: Vec<Passage> = [(0, 1), (1, 2), (2, 3), (1, 4), (4, 5)].iter()
+		.map(|&(from, to)| Passage { from, direction: DirectionType::NORTH, to })
+		.collect();
+	for seed in 0..50 {
+		let graph = PuzzleGraph::plan(&mut Rng::new(seed), &passages, 6, 4, 2);
+		assert_eq!(graph.puzzles.len(), 4);
+		assert!(graph.is_ordered(), "seed {}: {:?}", seed, graph);
+		assert_eq!(graph.puzzles.last().unwrap().passage, 2);
+
+		// Every key can be reached with only the puzzles before it solved
+		let mut reachable = [true, false, false, false, false, false];
+		for (n, puzzle) in graph.puzzles.iter().enumerate() {
+			for (p, passage) in passages.iter().enumerate() {
+				let solved = graph.puzzles[..n].iter().any(|z| z.passage == p);
+				let barred = graph.puzzles.iter().any(|z| z.passage == p);
+				if reachable[passage.from] && (solved || !barred) {
+					reachable[passage.to] = true;
+				}
+			}
+			assert!(reachable[passages[puzzle.passage].from], "seed {}: {:?}", seed, graph);
+			if let Some(room) = puzzle.key_room {
+				assert!(reachable[room], "seed {}: {:?}", seed, graph);
+			}
+		}
+	}
+}